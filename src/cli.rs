@@ -1,8 +1,12 @@
 use clap::{Parser, Subcommand};
 
+use crate::output::OutputFormat;
+
 use crate::commands::{
-    current::CurrentVersionCommand, install::InstallVersionCommand, list::ListVersionsCommand,
-    uninstall::UninstallVersionsCommand, update::UpdateVersionCommand,
+    cache::CacheCommand, config::ConfigCommand, current::CurrentVersionCommand,
+    doctor::DoctorCommand, install::InstallVersionCommand, link_custom::LinkCustomCommand,
+    list::ListVersionsCommand, run::RunVersionCommand, search::SearchVersionsCommand,
+    templates::TemplatesCommand, uninstall::UninstallVersionsCommand, update::UpdateVersionCommand,
 };
 
 #[derive(Parser)]
@@ -21,6 +25,22 @@ pub struct Args {
         global = true
     )]
     pub verbose: bool,
+
+    #[arg(
+        long,
+        help = "The format to output results in",
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        global = true
+    )]
+    pub output: OutputFormat,
+
+    #[arg(
+        long,
+        help = "Resolves versions purely from the cache of releases and the versions already installed, without accessing the network",
+        global = true
+    )]
+    pub offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -28,6 +48,11 @@ pub enum Commands {
     #[command(about = "Installs the specified version")]
     Install(InstallVersionCommand),
 
+    #[command(
+        about = "Links a custom build of Godot (e.g. one compiled from source), so it can be listed and activated like the versions gdman installs"
+    )]
+    LinkCustom(LinkCustomCommand),
+
     #[command(about = "Uninstalls the specified version(s) of Godot")]
     Uninstall(UninstallVersionsCommand),
 
@@ -39,4 +64,26 @@ pub enum Commands {
 
     #[command(about = "Update the currently-active version of Godot")]
     Update(UpdateVersionCommand),
+
+    #[command(
+        about = "Runs the specified version of Godot, installing it if necessary, without changing the active version"
+    )]
+    Run(RunVersionCommand),
+
+    #[command(about = "Lists the versions of Godot available to install")]
+    Search(SearchVersionsCommand),
+
+    #[command(about = "Manages the export templates installed for versions of Godot")]
+    Templates(TemplatesCommand),
+
+    #[command(about = "Manages the cache of releases fetched from GitHub")]
+    Cache(CacheCommand),
+
+    #[command(about = "Gets and sets the settings in the config file")]
+    Config(ConfigCommand),
+
+    #[command(
+        about = "Diagnoses (and optionally repairs) problems with the installed versions of Godot"
+    )]
+    Doctor(DoctorCommand),
 }
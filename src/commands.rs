@@ -1,6 +1,13 @@
+pub mod cache;
 pub mod common;
+pub mod config;
 pub mod current;
+pub mod doctor;
 pub mod install;
+pub mod link_custom;
 pub mod list;
+pub mod run;
+pub mod search;
+pub mod templates;
 pub mod uninstall;
 pub mod update;
@@ -0,0 +1,37 @@
+use clap::{Parser, Subcommand};
+
+use gdman::error::GdmanError;
+use gdman::github::releases_cache;
+
+use super::common::RunCommand;
+
+#[derive(Parser)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    command: CacheCommands,
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    #[command(about = "Deletes all cached releases, so they're fetched from GitHub next time")]
+    Clear(ClearCacheCommand),
+}
+
+impl RunCommand for CacheCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        return match self.command {
+            CacheCommands::Clear(clear) => clear.run().await,
+        };
+    }
+}
+
+#[derive(Parser)]
+struct ClearCacheCommand {}
+
+impl RunCommand for ClearCacheCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        releases_cache::clear()?;
+        log::info!("Cache cleared");
+        return Ok(());
+    }
+}
@@ -7,6 +7,19 @@ macro_rules! clap_enum_variants {
     }};
 }
 
+use gdman::error::GdmanError;
+
 pub trait RunCommand {
-    async fn run(self) -> Result<(), String>;
+    async fn run(self) -> Result<(), GdmanError>;
+}
+
+/// Moves any versions installed by older versions of gdman, for the commands which use the
+/// installed versions. This needs to happen before taking the lock, since it takes it too.
+pub async fn migrate_legacy_install() {
+    if let Err(e) = gdman::migrate_legacy_install().await {
+        log::warn!(
+            "Failed to migrate versions from the legacy install location\n{}",
+            e.report()
+        );
+    }
 }
@@ -0,0 +1,114 @@
+use clap::{Parser, Subcommand};
+
+use gdman::config::{self, ConfigKey};
+use gdman::error::GdmanError;
+
+use super::common::RunCommand;
+
+#[derive(Parser)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    command: ConfigCommands,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    #[command(about = "Prints the value of a setting")]
+    Get(GetConfigCommand),
+
+    #[command(about = "Sets the value of a setting in the config file")]
+    Set(SetConfigCommand),
+
+    #[command(about = "Removes a setting from the config file, so the default is used")]
+    Unset(UnsetConfigCommand),
+
+    #[command(about = "Lists the settings in the config file")]
+    List(ListConfigCommand),
+}
+
+impl RunCommand for ConfigCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        return match self.command {
+            ConfigCommands::Get(get) => get.run().await,
+            ConfigCommands::Set(set) => set.run().await,
+            ConfigCommands::Unset(unset) => unset.run().await,
+            ConfigCommands::List(list) => list.run().await,
+        };
+    }
+}
+
+#[derive(Parser)]
+struct GetConfigCommand {
+    #[arg(help = "The setting to get", value_enum)]
+    key: ConfigKey,
+}
+
+impl RunCommand for GetConfigCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        match config::resolve::<String>(self.key, None, None)? {
+            None => log::info!("{} is not set", self.key),
+            Some(value) => log::info!("{value}"),
+        }
+        return Ok(());
+    }
+}
+
+#[derive(Parser)]
+struct SetConfigCommand {
+    #[arg(help = "The setting to set", value_enum)]
+    key: ConfigKey,
+
+    #[arg(help = "The value to set it to")]
+    value: String,
+}
+
+impl RunCommand for SetConfigCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        config::set(self.key, Some(&self.value))?;
+        log::info!("Set {} to {}", self.key, self.value);
+        warn_if_overridden(self.key);
+        return Ok(());
+    }
+}
+
+#[derive(Parser)]
+struct UnsetConfigCommand {
+    #[arg(help = "The setting to remove", value_enum)]
+    key: ConfigKey,
+}
+
+impl RunCommand for UnsetConfigCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        config::set(self.key, None)?;
+        log::info!("Unset {}", self.key);
+        warn_if_overridden(self.key);
+        return Ok(());
+    }
+}
+
+#[derive(Parser)]
+struct ListConfigCommand {}
+
+impl RunCommand for ListConfigCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        log::info!("Config file: {}", config::get_config_file_path()?.display());
+        for key in <ConfigKey as clap::ValueEnum>::value_variants() {
+            let value = config::get(*key).unwrap_or("(not set)".to_owned());
+            let overridden = match std::env::var(key.env_var()) {
+                Err(_) => "".to_owned(),
+                Ok(v) => format!(" (overridden by {}={v})", key.env_var()),
+            };
+            log::info!("{key} = {value}{overridden}");
+        }
+        return Ok(());
+    }
+}
+
+fn warn_if_overridden(key: ConfigKey) {
+    if std::env::var(key.env_var()).is_ok() {
+        log::warn!(
+            "{key} is currently overridden by the {} environment variable",
+            key.env_var()
+        );
+    }
+}
@@ -1,23 +1,29 @@
 use clap::Parser;
 
-use crate::gdman;
+use crate::output::{self, VersionOutput};
+use gdman::error::GdmanError;
 
-use super::common::RunCommand;
+use super::common::{self, RunCommand};
 
 #[derive(Parser)]
 pub struct CurrentVersionCommand {}
 
 impl RunCommand for CurrentVersionCommand {
-    async fn run(self) -> Result<(), String> {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
         match gdman::get_current_version() {
-            Err(err) => {
-                if err.starts_with("Cant determine current version, godot link not found at") {
-                    log::info!("No version active");
-                    return Ok(());
+            Err(GdmanError::NoActiveVersion(_)) => {
+                if output::is_json() {
+                    return output::print_json(&None::<VersionOutput>);
                 }
-                return Err("".to_string());
+                log::info!("No version active");
+                return Ok(());
             }
+            Err(e) => return Err(e),
             Ok(current) => {
+                if output::is_json() {
+                    return output::print_json(&VersionOutput::from_installed(&current, true));
+                }
                 log::info!("{}", current.name_parts.version_name);
                 return Ok(());
             }
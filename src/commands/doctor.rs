@@ -0,0 +1,468 @@
+use std::{env, fs, path::Path};
+
+use clap::Parser;
+
+use gdman::{
+    custom, error::GdmanError, github::godot_repo::parse_version_name, lock, GodotVersionInfo,
+};
+
+use super::common::RunCommand;
+
+#[derive(Parser)]
+pub struct DoctorCommand {
+    #[arg(long, help = "Attempts to repair any problems found")]
+    fix: bool,
+}
+
+/// Keeps track of the problems found, fixing them if asked to
+struct Diagnosis {
+    fix: bool,
+    problems: usize,
+    fixed: usize,
+}
+
+impl Diagnosis {
+    fn ok(&self, message: &str) {
+        log::info!("OK: {message}");
+    }
+
+    /// Something worth knowing about, but which isn't a problem
+    fn note(&self, message: &str) {
+        log::info!("Note: {message}");
+    }
+
+    /// A problem that gdman can't fix, so the message should say what the user can do about it
+    fn problem(&mut self, message: &str) {
+        self.problems += 1;
+        log::warn!("Problem: {message}");
+    }
+
+    /// A problem that gdman can fix, if running with --fix
+    fn fixable(&mut self, message: &str, fix: impl FnOnce() -> Result<String, GdmanError>) {
+        self.problems += 1;
+        log::warn!("Problem: {message}");
+
+        if !self.fix {
+            return;
+        }
+
+        match fix() {
+            Err(e) => log::error!("Failed to fix: {}", e.report()),
+            Ok(fixed) => {
+                self.fixed += 1;
+                log::info!("Fixed: {fixed}");
+            }
+        }
+    }
+}
+
+impl RunCommand for DoctorCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        // Even without fixing anything, an install running at the same time
+        // would have its downloads and staging directory mistaken for leftovers
+        let _lock = lock::lock().await?;
+
+        let mut diagnosis = Diagnosis {
+            fix: self.fix,
+            problems: 0,
+            fixed: 0,
+        };
+
+        check_link_dir_on_path(&mut diagnosis)?;
+        check_leftover_downloads(&mut diagnosis)?;
+        let mut installed = check_installed_versions(&mut diagnosis)?;
+        installed.extend(check_custom_versions(&mut diagnosis)?);
+        check_link(&mut diagnosis, &installed)?;
+        check_templates(&diagnosis, &installed)?;
+
+        let remaining = diagnosis.problems - diagnosis.fixed;
+
+        if diagnosis.problems == 0 {
+            log::info!("No problems found");
+            return Ok(());
+        }
+        if remaining == 0 {
+            log::info!("Fixed all {} problem(s)", diagnosis.problems);
+            return Ok(());
+        }
+        if self.fix {
+            return Err(GdmanError::Other(format!(
+                "{remaining} of {} problem(s) could not be fixed",
+                diagnosis.problems
+            )));
+        }
+        return Err(GdmanError::Other(format!(
+            "Found {remaining} problem(s), run gdman doctor --fix to repair what can be repaired"
+        )));
+    }
+}
+
+fn check_link_dir_on_path(diagnosis: &mut Diagnosis) -> Result<(), GdmanError> {
+    let link_dir = gdman::get_link_dir()?;
+
+    let on_path = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).any(|dir| gdman::is_same_dir(&dir, &link_dir)))
+        .unwrap_or(false);
+
+    if on_path {
+        diagnosis.ok(&format!("{} is on the PATH", link_dir.display()));
+    } else {
+        diagnosis.problem(&format!(
+            "{} is not on the PATH, add it to be able to use the godot command",
+            link_dir.display()
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Looks for archives and staging directories left behind by failed or interrupted installs.
+/// Partial downloads are kept, since they're resumed by the next install of the same version.
+fn check_leftover_downloads(diagnosis: &mut Diagnosis) -> Result<(), GdmanError> {
+    let mut found = false;
+
+    if let Ok(entries) = fs::read_dir(gdman::get_downloads_dir()?) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // The ETag is kept alongside the partial download, to tell if it can be resumed
+            if name.ends_with(".part.etag") {
+                continue;
+            }
+            if name.ends_with(".part") {
+                diagnosis.note(&format!(
+                    "{} is a partial download, which is resumed by installing the same version again",
+                    path.display()
+                ));
+                continue;
+            }
+            found = true;
+            diagnosis.fixable(
+                &format!("{} was left behind by an earlier install", path.display()),
+                || remove_path(&path),
+            );
+        }
+    }
+
+    // Which entries are leftovers is decided before anything's deleted,
+    // so lock files aren't taken to be leftovers once their directory is
+    let staging_leftovers: Vec<_> = match fs::read_dir(gdman::get_staging_dir()?) {
+        Err(_) => Vec::new(),
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|path| !is_staging_dir_lock(path))
+            .collect(),
+    };
+
+    for path in staging_leftovers {
+        found = true;
+        diagnosis.fixable(
+            &format!("{} was left behind by an earlier install", path.display()),
+            || {
+                let removed = remove_path(&path)?;
+                let lock_path = gdman::get_staging_lock_path(&path);
+                if lock_path.exists() {
+                    remove_path(&lock_path)?;
+                }
+                return Ok(removed);
+            },
+        );
+    }
+
+    if !found {
+        diagnosis.ok("No leftover downloads found");
+    }
+
+    return Ok(());
+}
+
+/// Whether the path is the lock file of a staging directory, which is deleted along with it
+fn is_staging_dir_lock(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    return match name.strip_suffix(".lock") {
+        None => false,
+        Some(dir_name) => path.with_file_name(dir_name).is_dir(),
+    };
+}
+
+/// Checks everything in the versions directory is a complete installation,
+/// returning the versions which are
+fn check_installed_versions(
+    diagnosis: &mut Diagnosis,
+) -> Result<Vec<GodotVersionInfo>, GdmanError> {
+    let versions_dir = gdman::get_versions_dir()?;
+
+    let entries = match fs::read_dir(&versions_dir) {
+        Err(e) => return Err(GdmanError::io("Error reading versions directory", e)),
+        Ok(entries) => entries,
+    };
+
+    let mut installed: Vec<GodotVersionInfo> = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if path == gdman::get_staging_dir()? {
+            continue;
+        }
+
+        // Older versions of gdman downloaded archives into the versions directory
+        if name.ends_with(".zip") && path.is_file() {
+            diagnosis.fixable(
+                &format!("{} was left behind by an earlier install", path.display()),
+                || remove_path(&path),
+            );
+            continue;
+        }
+
+        let name_parts = match parse_version_name(&name) {
+            Ok(n) if path.is_dir() => n,
+            _ => {
+                diagnosis.problem(&format!(
+                    "{} wasn't installed by gdman, remove it if it's not needed",
+                    path.display()
+                ));
+                continue;
+            }
+        };
+
+        // Older versions of gdman also left archives within the version directory,
+        // which would stop the executable being found
+        let zip_path = path.join(format!("{name}.zip"));
+        if zip_path.is_file() {
+            diagnosis.fixable(
+                &format!(
+                    "{} was left behind by an earlier install",
+                    zip_path.display()
+                ),
+                || remove_path(&zip_path),
+            );
+            if zip_path.exists() {
+                continue;
+            }
+        }
+
+        let exe_path = match gdman::get_godot_exe_path(&path) {
+            Err(_) => {
+                // Installs are moved into place complete, so this could be anything
+                // (e.g. a layout gdman doesn't know about), and is only removed if empty
+                let is_empty = fs::read_dir(&path).is_ok_and(|mut e| e.next().is_none());
+                if is_empty {
+                    diagnosis.fixable(&format!("{name} is empty"), || remove_path(&path));
+                } else {
+                    diagnosis.problem(&format!(
+                        "The Godot executable couldn't be found in {}, remove it and install {name} again",
+                        path.display()
+                    ));
+                }
+                continue;
+            }
+            Ok(p) => p,
+        };
+
+        if !is_executable(&exe_path) {
+            diagnosis.fixable(&format!("{} is not executable", exe_path.display()), || {
+                gdman::ensure_executable(&exe_path)?;
+                Ok(format!("Made {} executable", exe_path.display()))
+            });
+        }
+
+        installed.push(GodotVersionInfo {
+            path,
+            name_parts,
+            custom: false,
+        });
+    }
+
+    diagnosis.ok(&format!(
+        "{} complete version(s) installed in {}",
+        installed.len(),
+        versions_dir.display()
+    ));
+
+    return Ok(installed);
+}
+
+/// Checks the custom builds still exist where they were linked from, returning those which do.
+/// Unlinking a missing build only removes gdman's record of it, so nothing is deleted.
+fn check_custom_versions(diagnosis: &mut Diagnosis) -> Result<Vec<GodotVersionInfo>, GdmanError> {
+    let mut linked: Vec<GodotVersionInfo> = Vec::new();
+
+    for custom in custom::get_custom_versions()? {
+        let name = custom.name_parts.version_name.clone();
+        if !custom.exe_path.is_file() {
+            diagnosis.fixable(
+                &format!(
+                    "Custom build {name} is linked to {}, which doesn't exist",
+                    custom.exe_path.display()
+                ),
+                || {
+                    custom::unlink_custom_version(&name)?;
+                    Ok(format!("Unlinked custom build {name}"))
+                },
+            );
+            continue;
+        }
+        linked.push(custom.to_version_info());
+    }
+
+    if !linked.is_empty() {
+        diagnosis.ok(&format!("{} custom build(s) linked", linked.len()));
+    }
+
+    return Ok(linked);
+}
+
+/// Checks the godot link points to one of the installed versions
+fn check_link(diagnosis: &mut Diagnosis, installed: &[GodotVersionInfo]) -> Result<(), GdmanError> {
+    let link_path = gdman::get_godot_link_path()?;
+
+    if fs::symlink_metadata(&link_path).is_err() {
+        diagnosis.note(&format!(
+            "No version is active, {} doesn't exist",
+            link_path.display()
+        ));
+        return Ok(());
+    }
+
+    let target = match gdman::get_link_target(&link_path) {
+        Err(_) => {
+            diagnosis.fixable(&format!("{} can't be read", link_path.display()), || {
+                remove_godot_link(&link_path)
+            });
+            return Ok(());
+        }
+        Ok(t) => t,
+    };
+
+    if let Some(custom) = custom::find_custom_version_by_exe(&target)?.filter(|c| {
+        installed
+            .iter()
+            .any(|v| v.custom && v.name_parts.version_name == c.name_parts.version_name)
+    }) {
+        diagnosis.ok(&format!(
+            "{} points to custom build {}",
+            link_path.display(),
+            custom.name_parts.version_name
+        ));
+        return Ok(());
+    }
+
+    let version_name = gdman::get_version_dir_from_exe_path(&target)
+        .ok()
+        .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()));
+    let installed_version = version_name.as_ref().and_then(|name| {
+        installed
+            .iter()
+            .find(|v| &v.name_parts.version_name == name)
+    });
+    let points_to_installed = installed_version
+        .and_then(|v| gdman::get_godot_exe_path(&v.path).ok())
+        .is_some_and(|exe| exe == target);
+
+    if points_to_installed {
+        diagnosis.ok(&format!(
+            "{} points to {}",
+            link_path.display(),
+            version_name.unwrap()
+        ));
+        return Ok(());
+    }
+
+    let message = match target.exists() {
+        true => format!(
+            "{} points to {}, which isn't a version installed by gdman",
+            link_path.display(),
+            target.display()
+        ),
+        false => format!(
+            "{} is broken, {} doesn't exist",
+            link_path.display(),
+            target.display()
+        ),
+    };
+
+    // If the version it was pointing to is installed (e.g. it's been moved), point it there instead
+    match installed_version {
+        Some(version) => {
+            let version_name = version.name_parts.version_name.clone();
+            diagnosis.fixable(&message, || {
+                gdman::set_active_godot_version(&version_name)?;
+                Ok(format!("Pointed {} to {version_name}", link_path.display()))
+            });
+        }
+        None if !target.exists() => {
+            diagnosis.fixable(&message, || remove_godot_link(&link_path));
+        }
+        None => diagnosis.problem(&message),
+    }
+
+    return Ok(());
+}
+
+fn check_templates(
+    diagnosis: &Diagnosis,
+    installed: &[GodotVersionInfo],
+) -> Result<(), GdmanError> {
+    let templates = gdman::get_installed_templates()?;
+    let current = gdman::get_current_version().ok();
+
+    for t in templates.iter() {
+        if !installed.iter().any(|v| t.matches(v)) {
+            diagnosis.note(&format!(
+                "Export templates {} don't match any version installed by gdman, run gdman templates uninstall --unused to remove them",
+                t.name
+            ));
+        }
+    }
+
+    if let Some(current) = current {
+        if !templates.iter().any(|t| t.matches(&current)) {
+            diagnosis.note(&format!(
+                "No export templates installed for {}, run gdman templates install to install them",
+                current.name_parts.version_name
+            ));
+        }
+    }
+
+    return Ok(());
+}
+
+fn remove_godot_link(link_path: &Path) -> Result<String, GdmanError> {
+    gdman::remove_link(&link_path.to_path_buf())?;
+    return Ok(format!(
+        "Removed {}, run gdman install to set an active version",
+        link_path.display()
+    ));
+}
+
+fn remove_path(path: &Path) -> Result<String, GdmanError> {
+    let result = match path.is_dir() {
+        true => fs::remove_dir_all(path),
+        false => fs::remove_file(path),
+    };
+    return match result {
+        Err(e) => Err(GdmanError::io(
+            format!("Failed to delete {}", path.display()),
+            e,
+        )),
+        Ok(_) => Ok(format!("Deleted {}", path.display())),
+    };
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    return fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0);
+}
+
+#[cfg(windows)]
+fn is_executable(_path: &Path) -> bool {
+    return true;
+}
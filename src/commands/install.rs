@@ -1,20 +1,26 @@
-use std::str::FromStr;
+use std::{fs, num::NonZeroUsize, path::PathBuf, str::FromStr};
 
 use reqwest::Client;
 
 use crate::clap_enum_variants;
-use crate::gd_semver::flatten_version;
-use crate::github::godot_repo as gd;
+use crate::progress_bars::ProgressBars;
+use gdman::config::{self, ConfigKey};
+use gdman::custom;
+use gdman::error::GdmanError;
+use gdman::gd_semver::flatten_version;
+use gdman::github::{godot_repo as gd, releases_cache};
+use gdman::lock;
+use gdman::project;
 
-use crate::common::{Architecture, Flavour, FromOS, Platform};
-use crate::{gd_semver::MaybeVersionOrVersionReq, gdman};
+use gdman::common::{Architecture, Channel, Flavour, FromOS, Platform};
+use gdman::gd_semver::MaybeVersionOrVersionReq;
 
 use clap::{Args, Parser};
 
-use super::common::RunCommand;
+use super::common::{self, RunCommand};
 
 #[derive(Args)]
-#[group(required = true, multiple = false)]
+#[group(required = false, multiple = false)]
 struct VersionOrLatest {
     #[arg(
         short,
@@ -24,8 +30,15 @@ struct VersionOrLatest {
     )]
     latest: bool,
 
-    #[arg(short, long, help = "Specifies the version to install", value_parser=MaybeVersionOrVersionReq::from_str, group="version_or_latest")]
+    #[arg(short, long, help = "Specifies the version to install. If neither this nor --latest are specified, the version is read from the project's .godot-version or gdman.toml file", value_parser=MaybeVersionOrVersionReq::from_str, group="version_or_latest")]
     version: Option<MaybeVersionOrVersionReq>,
+
+    #[arg(
+        long,
+        help = "Sets the custom build linked under the name via link-custom active",
+        group = "version_or_latest"
+    )]
+    custom: Option<String>,
 }
 
 #[derive(Parser)]
@@ -33,54 +46,300 @@ pub struct InstallVersionCommand {
     #[command(flatten)]
     version_or_latest: VersionOrLatest,
 
-    #[arg(short, long, help = "Specifies the target architecture", value_enum, default_value_t=Architecture::from_os().unwrap(), value_parser=clap_enum_variants!(Architecture))]
-    architecture: Architecture,
+    #[arg(short, long, help = "Specifies the target architecture [default: the current system's architecture, or the architecture setting]", value_enum, value_parser=clap_enum_variants!(Architecture))]
+    architecture: Option<Architecture>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of version to install [default: standard, or the flavour setting]", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(short, long, help = "The least stable channel of release to consider when looking for the latest version matching --version or --latest [default: stable, or the channel setting]", value_enum, value_parser=clap_enum_variants!(Channel))]
+    channel: Option<Channel>,
+
+    #[arg(
+        long,
+        help = "Skips verifying the downloaded archive against the checksums published with the release"
+    )]
+    skip_verify: bool,
+
+    #[arg(
+        long,
+        help = "Also installs the export templates for the version [default: the with-templates setting]"
+    )]
+    with_templates: bool,
 
-    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of version to install", value_enum, default_value_t=Flavour::Standard, value_parser=clap_enum_variants!(Flavour))]
-    flavour: Flavour,
+    #[command(flatten)]
+    archive: Archive,
+}
+
+#[derive(Args)]
+#[group(
+    required = false,
+    multiple = true,
+    conflicts_with = "version_or_latest"
+)]
+struct Archive {
+    #[arg(
+        long,
+        help = "Installs from a zip archive on disk instead of a release, e.g. a custom build. The archive is left in place",
+        group = "archive"
+    )]
+    from_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Installs from a zip archive at the URL instead of a release",
+        group = "archive"
+    )]
+    from_url: Option<String>,
+
+    #[arg(
+        long,
+        help = "The name to install the archive as, for when its file name doesn't follow Godot's naming convention. The name must follow it, e.g. Godot_v4.3-stable_linux.x86_64, as that's how gdman tells what version it is [default: the archive's file name]",
+        requires = "archive"
+    )]
+    name: Option<String>,
 }
 
 impl RunCommand for InstallVersionCommand {
-    async fn run(self) -> Result<(), String> {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let _lock = lock::lock().await?;
+
+        let with_templates = config::resolve(
+            ConfigKey::WithTemplates,
+            self.with_templates.then_some(true),
+            None,
+        )?
+        .unwrap_or(false);
+        let keep = config::resolve::<usize>(ConfigKey::Keep, None, None)?;
+        let parallelism = config::resolve::<NonZeroUsize>(ConfigKey::Parallelism, None, None)?
+            .map_or(1, |p| p.get());
+
+        let client = Client::new();
+        let progress = ProgressBars::default();
+
+        // With more than one download at a time, the templates are downloaded along with the version
+        let installed_templates = match (&self.archive.from_file, &self.archive.from_url) {
+            (None, None) => {
+                self.install_version(&client, &progress, with_templates && parallelism > 1)
+                    .await?
+            }
+            _ => {
+                self.install_archive(&client, &progress).await?;
+                false
+            }
+        };
+
+        if let Some(keep) = keep {
+            gdman::uninstall_old_versions(keep)?;
+        }
+
+        if with_templates && !installed_templates {
+            let current = gdman::get_current_version()?;
+            gdman::install_templates_if_missing(
+                &current.name_parts,
+                &client,
+                self.skip_verify,
+                &progress,
+            )
+            .await?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl InstallVersionCommand {
+    /// Installs the version specified by --version or --latest, or pinned by the project,
+    /// or sets the custom build specified by --custom active.
+    /// If it needs downloading and `with_templates` is set, its export templates are downloaded
+    /// at the same time, returning whether they were.
+    async fn install_version(
+        &self,
+        client: &Client,
+        progress: &ProgressBars,
+        with_templates: bool,
+    ) -> Result<bool, GdmanError> {
+        // Custom builds are already where they were linked from, so there's nothing to install
+        if let Some(name) = &self.version_or_latest.custom {
+            if custom::find_custom_version(name)?.is_none() {
+                return Err(GdmanError::NotInstalled(name.to_owned()));
+            }
+            gdman::set_active_godot_version(name)?;
+            return Ok(false);
+        }
+
         let platform = Platform::from_os()?;
 
-        let (version_input, version_like, version_exact) =
-            flatten_version(&self.version_or_latest.version);
+        // When no version is specified, fall back to the version pinned by the project
+        let project_version = match &self.version_or_latest {
+            VersionOrLatest {
+                latest: false,
+                version: None,
+                ..
+            } => match project::find_project_version()? {
+                None => return Err(GdmanError::InvalidInput(
+                    "--version, --latest, --custom, --from-file or --from-url must be specified when there's no .godot-version or gdman.toml file in the current directory or its parents"
+                        .to_owned(),
+                )),
+                Some(p) => {
+                    log::info!(
+                        "Using version {} from {}",
+                        p.version.input_str,
+                        p.path.display()
+                    );
+                    Some(p)
+                }
+            },
+            _ => None,
+        };
+
+        let version = match &project_version {
+            Some(p) => Some(p.version.clone()),
+            None => self.version_or_latest.version.clone(),
+        };
+
+        let architecture = match config::resolve(
+            ConfigKey::Architecture,
+            self.architecture.clone(),
+            project_version
+                .as_ref()
+                .and_then(|p| p.architecture.clone()),
+        )? {
+            Some(a) => a,
+            None => Architecture::from_os()?,
+        };
+
+        let flavour = config::resolve(
+            ConfigKey::Flavour,
+            self.flavour,
+            project_version.as_ref().and_then(|p| p.flavour),
+        )?
+        .unwrap_or(Flavour::Standard);
+
+        let channel =
+            config::resolve(ConfigKey::Channel, self.channel, None)?.unwrap_or(Channel::Stable);
 
-        if let Some(_) = version_exact {
-            if gdman::activate_by_parts_if_installed(
-                &version_input,
+        let (version_input, _, version_exact) = flatten_version(&version);
+
+        let activated_installed = match &version {
+            // When offline, settle for the latest installed version matching the constraint
+            Some(version) if releases_cache::is_offline() => {
+                match gdman::find_installed_version(version, &platform, &architecture, &flavour)? {
+                    None => false,
+                    Some(installed) => {
+                        let version_name = installed.path.file_name().unwrap().to_string_lossy();
+                        gdman::activate_by_name_if_installed(&version_name)?
+                    }
+                }
+            }
+            _ => {
+                version_exact.is_some()
+                    && gdman::activate_by_parts_if_installed(
+                        &version_input,
+                        &platform,
+                        &architecture,
+                        &flavour,
+                    )?
+            }
+        };
+
+        if !activated_installed {
+            let release = gdman::resolve_release(
+                &version,
                 &platform,
-                &self.architecture,
-                &self.flavour,
-            )? {
-                return Ok(());
+                &architecture,
+                &flavour,
+                &channel,
+                client,
+            )
+            .await?;
+
+            let asset = release.assets.first().unwrap();
+            let version_name = asset.name.trim_end_matches(".zip");
+
+            if !gdman::activate_by_name_if_installed(version_name)? {
+                if !with_templates {
+                    gdman::install_release(&release, client, self.skip_verify, progress).await?;
+                    gdman::set_active_godot_version(version_name)?;
+                    return Ok(false);
+                }
+
+                let name_parts = gd::parse_version_name(version_name)?;
+                let templates_progress = progress.alongside();
+                let (installed, installed_templates) = tokio::join!(
+                    gdman::install_release(&release, client, self.skip_verify, progress),
+                    gdman::install_templates_if_missing(
+                        &name_parts,
+                        client,
+                        self.skip_verify,
+                        &templates_progress
+                    )
+                );
+                installed?;
+                gdman::set_active_godot_version(version_name)?;
+                installed_templates?;
+                return Ok(true);
             }
         }
 
-        let client = Client::new();
-
-        let release = gd::find_release_with_asset(
-            &version_exact,
-            &version_like,
-            &platform,
-            &self.architecture,
-            &self.flavour,
-            &client,
-        )
-        .await?;
+        return Ok(false);
+    }
 
-        let asset = release.assets.first().unwrap();
-        let version_name = asset.name.trim_end_matches(".zip");
+    /// Installs the archive specified by --from-file or --from-url
+    async fn install_archive(
+        &self,
+        client: &Client,
+        progress: &ProgressBars,
+    ) -> Result<(), GdmanError> {
+        let archive_name = match (&self.archive.from_file, &self.archive.from_url) {
+            (Some(path), _) => path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            (None, Some(url)) => gdman::get_url_file_name(url)?,
+            (None, None) => return Ok(()),
+        };
+        let version_name = gdman::get_archive_version_name(&archive_name, &self.archive.name)?;
 
-        if gdman::activate_by_name_if_installed(version_name)? {
+        if gdman::activate_by_name_if_installed(&version_name)? {
+            log::info!("{version_name} is already installed, uninstall it first to reinstall it");
             return Ok(());
         }
 
-        gdman::download_godot_version(version_name, &client, &asset.browser_download_url).await?;
+        match (&self.archive.from_file, &self.archive.from_url) {
+            (Some(path), _) => {
+                if !path.is_file() {
+                    return Err(GdmanError::InvalidInput(format!(
+                        "Archive {} doesn't exist",
+                        path.display()
+                    )));
+                }
+                gdman::install_archive(path, &version_name, progress).await?;
+            }
+            (None, Some(url)) => {
+                if releases_cache::is_offline() {
+                    return Err(GdmanError::Offline(format!(
+                        "{url} can't be downloaded while offline"
+                    )));
+                }
+
+                let archive_path = gdman::get_downloads_dir()?.join(&archive_name);
+                log::info!("Getting {url}");
+                gdman::download_file(client, url, &archive_path, progress).await?;
 
-        gdman::set_active_godot_version(&version_name)?;
+                let result = gdman::install_archive(&archive_path, &version_name, progress).await;
 
-        return Ok(());
+                log::trace!("Deleting archive {}", archive_path.display());
+                if let Err(e) = fs::remove_file(&archive_path) {
+                    log::warn!("Failed to delete archive {}\n{e}", archive_path.display());
+                }
+
+                result?;
+            }
+            (None, None) => return Ok(()),
+        }
+
+        return gdman::set_active_godot_version(&version_name);
     }
 }
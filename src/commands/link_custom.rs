@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::clap_enum_variants;
+use gdman::{
+    common::{Architecture, Flavour, FromOS},
+    custom,
+    error::GdmanError,
+    lock,
+};
+
+use super::common::{self, RunCommand};
+
+#[derive(Parser)]
+pub struct LinkCustomCommand {
+    #[arg(
+        help = "The name to give the build, which it's listed and activated as. Linking another build with the same name replaces it"
+    )]
+    name: String,
+
+    #[arg(
+        help = "The path of the build's Godot executable, or of a directory containing it laid out like the versions gdman installs"
+    )]
+    path: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "The version of Godot the build is based on, e.g. 4.3 or 4.4-beta1"
+    )]
+    version: String,
+
+    #[arg(short, long, help = "The architecture the build is for [default: the current system's architecture]", value_enum, value_parser=clap_enum_variants!(Architecture))]
+    architecture: Option<Architecture>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of the build [default: standard]", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(
+        long,
+        help = "Sets the build active once it's linked. It can be set active later with gdman install --custom <NAME>"
+    )]
+    activate: bool,
+}
+
+impl RunCommand for LinkCustomCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let _lock = lock::lock().await?;
+
+        let architecture = match self.architecture {
+            Some(a) => a,
+            None => Architecture::from_os()?,
+        };
+        let flavour = self.flavour.unwrap_or(Flavour::Standard);
+
+        let custom = custom::link_custom_version(
+            &self.name,
+            &self.path,
+            &self.version,
+            &flavour,
+            &architecture,
+        )?;
+
+        log::info!(
+            "Linked custom build {} to {}",
+            self.name,
+            custom.exe_path.display()
+        );
+
+        if !self.activate {
+            return Ok(());
+        }
+
+        return gdman::set_active_godot_version(&self.name);
+    }
+}
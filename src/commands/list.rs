@@ -1,23 +1,65 @@
-use crate::gdman;
+use crate::output::{self, VersionOutput};
 use clap::Parser;
+use gdman::{custom, error::GdmanError};
 
-use super::common::RunCommand;
+use super::common::{self, RunCommand};
 
 #[derive(Parser)]
 pub struct ListVersionsCommand {}
 
 impl RunCommand for ListVersionsCommand {
-    async fn run(self) -> Result<(), String> {
-        let versions = gdman::get_installed_versions()?;
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let contents = gdman::read_versions_dir()?;
+        let mut versions = contents.versions;
+        versions.extend(
+            custom::get_custom_versions()?
+                .iter()
+                .map(|c| c.to_version_info()),
+        );
 
-        if versions.len() == 0 {
+        if output::is_json() {
+            let current_name = gdman::get_current_version()
+                .ok()
+                .map(|c| c.name_parts.version_name);
+            let versions: Vec<VersionOutput> = versions
+                .iter()
+                .map(|v| {
+                    let active = current_name.as_ref() == Some(&v.name_parts.version_name);
+                    VersionOutput::from_installed(v, active)
+                })
+                .collect();
+            for path in contents.unrecognized {
+                log::warn!(
+                    "Ignoring {}, it's not a recognized version of Godot",
+                    path.display()
+                );
+            }
+            return output::print_json(&versions);
+        }
+
+        if versions.is_empty() {
             log::info!("No versions installed");
         } else {
             for version in versions {
-                log::info!("{}", version.name_parts.version_name);
+                match version.custom {
+                    true => log::info!(
+                        "{} (custom build at {})",
+                        version.name_parts.version_name,
+                        version.path.display()
+                    ),
+                    false => log::info!("{}", version.name_parts.version_name),
+                }
             }
         }
 
+        // Anything else in the versions directory is listed so the user knows it's there,
+        // but gdman won't do anything with it
+        for path in contents.unrecognized {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            log::info!("{name} (unrecognized)");
+        }
+
         return Ok(());
     }
 }
@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use clap::Parser;
+use reqwest::Client;
+
+use crate::{clap_enum_variants, progress_bars::ProgressBars};
+use gdman::{
+    common::{Architecture, Channel, Flavour, FromOS, Platform},
+    config::{self, ConfigKey},
+    error::GdmanError,
+    gd_semver::MaybeVersionOrVersionReq,
+    lock, project,
+};
+
+use super::common::{self, RunCommand};
+
+#[derive(Parser)]
+pub struct RunVersionCommand {
+    #[arg(short, long, help = "Specifies the version to run. If not specified, the version is read from the project's .godot-version or gdman.toml file, falling back to the currently-active version", value_parser=MaybeVersionOrVersionReq::from_str)]
+    version: Option<MaybeVersionOrVersionReq>,
+
+    #[arg(short, long, help = "Specifies the target architecture [default: the current system's architecture, or the architecture setting]", value_enum, value_parser=clap_enum_variants!(Architecture))]
+    architecture: Option<Architecture>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of version to run [default: standard, or the flavour setting]", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(short, long, help = "The least stable channel of release to consider when looking for the latest version matching --version, if the version needs to be installed [default: stable, or the channel setting]", value_enum, value_parser=clap_enum_variants!(Channel))]
+    channel: Option<Channel>,
+
+    #[arg(
+        long,
+        help = "Skips verifying the downloaded archive against the checksums published with the release, if the version needs to be installed"
+    )]
+    skip_verify: bool,
+
+    #[arg(
+        last = true,
+        help = "Arguments to pass to Godot, e.g. gdman run -- --headless --export-release Linux build/game.x86_64"
+    )]
+    args: Vec<String>,
+}
+
+impl RunCommand for RunVersionCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let platform = Platform::from_os()?;
+
+        let project_version = match self.version {
+            Some(_) => None,
+            None => project::find_project_version()?,
+        };
+
+        let version = match (&self.version, &project_version) {
+            (Some(v), _) => v.clone(),
+            (None, Some(p)) => {
+                log::trace!(
+                    "Using version {} from {}",
+                    p.version.input_str,
+                    p.path.display()
+                );
+                p.version.clone()
+            }
+            (None, None) => {
+                log::trace!("No version specified, running the currently-active version");
+                let current = gdman::get_current_version()?;
+                return gdman::exec_godot(&gdman::get_version_exe_path(&current)?, &self.args);
+            }
+        };
+
+        let architecture = match config::resolve(
+            ConfigKey::Architecture,
+            self.architecture.clone(),
+            project_version
+                .as_ref()
+                .and_then(|p| p.architecture.clone()),
+        )? {
+            Some(a) => a,
+            None => Architecture::from_os()?,
+        };
+
+        let flavour = config::resolve(
+            ConfigKey::Flavour,
+            self.flavour,
+            project_version.as_ref().and_then(|p| p.flavour),
+        )?
+        .unwrap_or(Flavour::Standard);
+
+        let version_dir =
+            match gdman::find_installed_version(&version, &platform, &architecture, &flavour)? {
+                Some(installed) => installed.path,
+                None => {
+                    log::info!(
+                        "No installed version matches {}, installing it",
+                        version.input_str
+                    );
+
+                    let _lock = lock::lock().await?;
+
+                    let channel = config::resolve(ConfigKey::Channel, self.channel, None)?
+                        .unwrap_or(Channel::Stable);
+                    let client = Client::new();
+
+                    let release = gdman::resolve_release(
+                        &Some(version),
+                        &platform,
+                        &architecture,
+                        &flavour,
+                        &channel,
+                        &client,
+                    )
+                    .await?;
+
+                    gdman::install_release(
+                        &release,
+                        &client,
+                        self.skip_verify,
+                        &ProgressBars::default(),
+                    )
+                    .await?
+                }
+            };
+
+        let exe_path = gdman::get_godot_exe_path(&version_dir)?;
+
+        return gdman::exec_godot(&exe_path, &self.args);
+    }
+}
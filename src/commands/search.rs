@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use clap::Parser;
+use indicatif::HumanBytes;
+use reqwest::Client;
+
+use crate::{
+    clap_enum_variants,
+    output::{self, RemoteVersionOutput},
+};
+use gdman::{
+    common::{Architecture, Channel, Flavour, FromOS, Platform},
+    config::{self, ConfigKey},
+    error::GdmanError,
+    gd_semver::{flatten_version, parse_semver_version, MaybeVersionOrVersionReq},
+    github::godot_repo as gd,
+};
+
+use super::common::RunCommand;
+
+#[derive(Parser)]
+pub struct SearchVersionsCommand {
+    #[arg(short, long, help = "Specifies the version(s) to search for [default: all versions]", value_parser=MaybeVersionOrVersionReq::from_str)]
+    version: Option<MaybeVersionOrVersionReq>,
+
+    #[arg(short, long, help = "Specifies the target architecture [default: the current system's architecture, or the architecture setting]", value_enum, value_parser=clap_enum_variants!(Architecture))]
+    architecture: Option<Architecture>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of version to search for [default: standard, or the flavour setting]", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(short, long, help = "The least stable channel of release to search for [default: stable, or the channel setting]", value_enum, value_parser=clap_enum_variants!(Channel))]
+    channel: Option<Channel>,
+}
+
+impl RunCommand for SearchVersionsCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        let platform = Platform::from_os()?;
+        let architecture = match config::resolve(ConfigKey::Architecture, self.architecture, None)?
+        {
+            Some(a) => a,
+            None => Architecture::from_os()?,
+        };
+        let flavour =
+            config::resolve(ConfigKey::Flavour, self.flavour, None)?.unwrap_or(Flavour::Standard);
+        let channel =
+            config::resolve(ConfigKey::Channel, self.channel, None)?.unwrap_or(Channel::Stable);
+
+        let (_, version_like, version_exact) = flatten_version(&self.version);
+
+        let client = Client::new();
+
+        let releases = gd::find_releases_with_asset(
+            &version_exact,
+            &version_like,
+            &platform,
+            &architecture,
+            &flavour,
+            &channel,
+            &client,
+        )
+        .await?;
+
+        let installed_names: Vec<String> = gdman::get_installed_versions()?
+            .into_iter()
+            .map(|v| v.name_parts.version_name)
+            .collect();
+        let current_name = gdman::get_current_version()
+            .ok()
+            .map(|c| c.name_parts.version_name);
+
+        let versions: Vec<RemoteVersionOutput> = releases
+            .iter()
+            .map(|release| {
+                let asset = release.assets.first().unwrap();
+                let name = asset.name.trim_end_matches(".zip").to_owned();
+                let version =
+                    parse_semver_version(&release.tag_name, &Some(vec!["stable".to_owned()]))
+                        .map(|v| v.to_string())
+                        .unwrap_or(release.tag_name.clone());
+                RemoteVersionOutput {
+                    installed: installed_names.contains(&name),
+                    active: current_name.as_ref() == Some(&name),
+                    name,
+                    version,
+                    version_string: release.tag_name.clone(),
+                    flavour: flavour.to_string(),
+                    platform: platform.to_string().to_lowercase(),
+                    architecture: architecture.to_string(),
+                    published_at: release.published_at,
+                    size: asset.size,
+                }
+            })
+            .collect();
+
+        if output::is_json() {
+            return output::print_json(&versions);
+        }
+
+        if versions.is_empty() {
+            log::info!("No matching versions found");
+            return Ok(());
+        }
+
+        for version in versions {
+            let status = match (version.active, version.installed) {
+                (true, _) => " (active)",
+                (false, true) => " (installed)",
+                _ => "",
+            };
+            log::info!(
+                "{:<45} {}  {:>10}{status}",
+                version.name,
+                version.published_at.format("%Y-%m-%d"),
+                HumanBytes(version.size as u64).to_string(),
+            );
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,228 @@
+use std::str::FromStr;
+
+use clap::{Parser, Subcommand};
+use reqwest::Client;
+
+use crate::{clap_enum_variants, progress_bars::ProgressBars};
+use gdman::{
+    common::{Channel, Flavour},
+    config::{self, ConfigKey},
+    error::GdmanError,
+    gd_semver::{flatten_version, MaybeVersionOrVersionReq},
+    github::godot_repo as gd,
+    lock, TemplatesInfo,
+};
+
+use super::common::{self, RunCommand};
+
+#[derive(Parser)]
+pub struct TemplatesCommand {
+    #[command(subcommand)]
+    command: TemplatesCommands,
+}
+
+#[derive(Subcommand)]
+enum TemplatesCommands {
+    #[command(about = "Installs the export templates for the specified version of Godot")]
+    Install(InstallTemplatesCommand),
+
+    #[command(about = "Lists the export templates currently installed on the system")]
+    List(ListTemplatesCommand),
+
+    #[command(about = "Uninstalls the specified export templates")]
+    Uninstall(UninstallTemplatesCommand),
+}
+
+impl RunCommand for TemplatesCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        return match self.command {
+            TemplatesCommands::Install(install) => install.run().await,
+            TemplatesCommands::List(list) => list.run().await,
+            TemplatesCommands::Uninstall(uninstall) => uninstall.run().await,
+        };
+    }
+}
+
+#[derive(Parser)]
+struct InstallTemplatesCommand {
+    #[arg(short, long, help = "Specifies the version to install export templates for [default: the currently-active version]", value_parser=MaybeVersionOrVersionReq::from_str)]
+    version: Option<MaybeVersionOrVersionReq>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of export templates to install [default: the flavour of the currently-active version]", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(short, long, help = "The least stable channel of release to consider when looking for the latest version matching --version [default: stable, or the channel setting]", value_enum, value_parser=clap_enum_variants!(Channel))]
+    channel: Option<Channel>,
+
+    #[arg(
+        long,
+        help = "Skips verifying the downloaded archive against the checksums published with the release"
+    )]
+    skip_verify: bool,
+}
+
+impl RunCommand for InstallTemplatesCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        let _lock = lock::lock().await?;
+
+        let client = Client::new();
+
+        if self.version.is_none() {
+            let current = gdman::get_current_version()?;
+            let mut name_parts = current.name_parts.clone();
+            if let Some(flavour) = self.flavour {
+                name_parts.flavour = flavour;
+            }
+            gdman::install_templates_if_missing(
+                &name_parts,
+                &client,
+                self.skip_verify,
+                &ProgressBars::default(),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let (_, version_like, version_exact) = flatten_version(&self.version);
+        let flavour = self.flavour.unwrap_or(Flavour::Standard);
+        let channel =
+            config::resolve(ConfigKey::Channel, self.channel, None)?.unwrap_or(Channel::Stable);
+
+        let release = gd::find_release_with_templates(
+            &version_exact,
+            &version_like,
+            &flavour,
+            &channel,
+            &client,
+        )
+        .await?;
+
+        gdman::install_templates(
+            &release,
+            &flavour,
+            &client,
+            self.skip_verify,
+            &ProgressBars::default(),
+        )
+        .await?;
+
+        return Ok(());
+    }
+}
+
+#[derive(Parser)]
+struct ListTemplatesCommand {}
+
+impl RunCommand for ListTemplatesCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        let templates = gdman::get_installed_templates()?;
+
+        if templates.is_empty() {
+            log::info!("No export templates installed");
+            return Ok(());
+        }
+
+        let installed_versions = gdman::get_installed_versions()?;
+        let current_version = gdman::get_current_version().ok();
+
+        for t in templates {
+            if current_version.as_ref().is_some_and(|c| t.matches(c)) {
+                log::info!("{} (active)", t.name);
+            } else if !installed_versions.iter().any(|v| t.matches(v)) {
+                log::info!("{} (no matching version installed)", t.name);
+            } else {
+                log::info!("{}", t.name);
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[derive(Parser)]
+struct UninstallTemplatesCommand {
+    #[arg(short, long, help = "Specifies the version(s) to uninstall export templates for", value_parser=MaybeVersionOrVersionReq::from_str, group = "templates_filter")]
+    version: Option<MaybeVersionOrVersionReq>,
+
+    #[arg(short, long, help = "The \"flavour\" (for lack of a better name) of export templates to uninstall", value_enum, value_parser=clap_enum_variants!(Flavour))]
+    flavour: Option<Flavour>,
+
+    #[arg(long, help = "Allows multiple export templates to be uninstalled")]
+    force: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Uninstall all export templates which don't match an installed version of Godot",
+        group = "templates_filter"
+    )]
+    unused: bool,
+}
+
+impl RunCommand for UninstallTemplatesCommand {
+    async fn run(self) -> Result<(), GdmanError> {
+        let _lock = lock::lock().await?;
+
+        if !self.unused && self.version.is_none() {
+            return Err(GdmanError::InvalidInput(
+                "--version or --unused must be specified".to_owned(),
+            ));
+        }
+
+        let installed_versions = gdman::get_installed_versions()?;
+        let mut candidates: Vec<TemplatesInfo> = Vec::new();
+
+        for templates in gdman::get_installed_templates()? {
+            if self.unused {
+                if !installed_versions.iter().any(|v| templates.matches(v)) {
+                    candidates.push(templates);
+                }
+                continue;
+            }
+
+            let v = self.version.clone().unwrap();
+            match v.version_exact {
+                Some(exact) => {
+                    if exact != templates.version {
+                        continue;
+                    };
+                }
+                None => {
+                    if !v.version_like.matches(&templates.version) {
+                        continue;
+                    }
+                }
+            }
+            if let Some(flavour) = &self.flavour {
+                if flavour != &templates.flavour {
+                    continue;
+                }
+            }
+            candidates.push(templates);
+        }
+
+        if candidates.is_empty() {
+            log::info!("No export templates found to uninstall");
+            return Ok(());
+        }
+
+        for templates in &candidates {
+            log::info!("Found export templates {} for uninstall", templates.name);
+        }
+
+        if candidates.len() > 1 && !self.force && !self.unused {
+            return Err(GdmanError::InvalidInput(
+                "Multiple export templates can only be uninstalled with the --force or --unused args"
+                    .to_owned(),
+            ));
+        }
+
+        for templates in candidates {
+            gdman::uninstall_templates(&templates)?;
+            log::info!("Uninstalled export templates {}", templates.name);
+        }
+
+        return Ok(());
+    }
+}
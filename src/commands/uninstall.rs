@@ -2,14 +2,15 @@ use std::str::FromStr;
 
 use clap::Parser;
 
-use crate::{
-    clap_enum_variants,
+use crate::clap_enum_variants;
+use gdman::{
     common::{Architecture, Flavour},
+    error::GdmanError,
     gd_semver::MaybeVersionOrVersionReq,
-    gdman::{self, GodotVersionInfo},
+    lock, GodotVersionInfo,
 };
 
-use super::common::RunCommand;
+use super::common::{self, RunCommand};
 
 #[derive(Parser)]
 pub struct UninstallVersionsCommand {
@@ -35,14 +36,19 @@ pub struct UninstallVersionsCommand {
 }
 
 impl RunCommand for UninstallVersionsCommand {
-    async fn run(self) -> Result<(), String> {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let _lock = lock::lock().await?;
+
         let current_version = gdman::get_current_version()?;
-        let current_version_dir = current_version.path.parent().unwrap().to_path_buf();
-        let mut err: Option<String> = None;
+        let current_version_dir = current_version.path;
+        let mut err: Option<GdmanError> = None;
         let mut candidates: Vec<GodotVersionInfo> = Vec::new();
 
         if !self.unused && self.version.is_none() {
-            return Err("--version or --unused must be specified".to_owned());
+            return Err(GdmanError::InvalidInput(
+                "--version or --unused must be specified".to_owned(),
+            ));
         }
 
         for version in gdman::get_installed_versions()? {
@@ -90,18 +96,18 @@ impl RunCommand for UninstallVersionsCommand {
         }
 
         if candidate_count > 1 && !&self.force && !&self.unused {
-            return Err(
+            return Err(GdmanError::InvalidInput(
                 "Multiple versions can only be uninstalled with the --force or --unused args"
                     .to_owned(),
-            );
+            ));
         }
 
         for version in candidates {
             if version.path == current_version_dir {
-                err = Some(format!(
+                err = Some(GdmanError::InvalidInput(format!(
                     "Cannot uninstall version {} because it is currently active",
                     version.name_parts.version_name
-                ));
+                )));
             } else {
                 gdman::uninstall_version(&version)?;
                 log::info!("Uninstalled version {}", version.name_parts.version_name);
@@ -1,10 +1,16 @@
 use clap::{Args, Parser};
 use reqwest::Client;
 
-use crate::gdman;
-use crate::github::godot_repo as gd;
+use crate::clap_enum_variants;
+use crate::progress_bars::ProgressBars;
+use gdman::common::Channel;
+use gdman::config::{self, ConfigKey};
+use gdman::error::GdmanError;
+use gdman::gd_semver::get_channel;
+use gdman::github::godot_repo as gd;
+use gdman::lock;
 
-use super::common::RunCommand;
+use super::common::{self, RunCommand};
 
 #[derive(Args)]
 #[group(required = true, multiple = false)]
@@ -40,11 +46,39 @@ pub struct UpdateVersionCommand {
         default_value_t = false
     )]
     uninstall: bool,
+
+    #[arg(short, long, help = "The least stable channel of release to update to [default: the channel of the currently-active version]", value_enum, value_parser=clap_enum_variants!(Channel))]
+    channel: Option<Channel>,
+
+    #[arg(
+        long,
+        help = "Skips verifying the downloaded archive against the checksums published with the release"
+    )]
+    skip_verify: bool,
+
+    #[arg(
+        long,
+        help = "Also installs the export templates for the updated version [default: the with-templates setting]"
+    )]
+    with_templates: bool,
 }
 
 impl RunCommand for UpdateVersionCommand {
-    async fn run(self) -> Result<(), String> {
+    async fn run(self) -> Result<(), GdmanError> {
+        common::migrate_legacy_install().await;
+        let _lock = lock::lock().await?;
+
         let current = gdman::get_current_version()?;
+
+        // There's nothing to update a custom build to, and it may carry patches
+        // that an official release wouldn't have
+        if current.custom {
+            return Err(GdmanError::InvalidInput(format!(
+                "{} is a custom build, so can't be updated. Use gdman install to switch to an official version",
+                current.name_parts.version_name
+            )));
+        }
+
         let current_version_string = current.name_parts.version.to_string();
 
         log::trace!(
@@ -78,7 +112,24 @@ impl RunCommand for UpdateVersionCommand {
             log::trace!("Looking for latest version");
         }
 
+        // The channel of the current version takes the place of the project file,
+        // so updating doesn't switch channels unless asked to
+        let channel = config::resolve(
+            ConfigKey::Channel,
+            self.channel,
+            Some(get_channel(&current.name_parts.version)),
+        )?
+        .unwrap_or(Channel::Stable);
+        let with_templates = config::resolve(
+            ConfigKey::WithTemplates,
+            self.with_templates.then_some(true),
+            None,
+        )?
+        .unwrap_or(false);
+        let keep = config::resolve::<usize>(ConfigKey::Keep, None, None)?;
+
         let client = Client::new();
+        let progress = ProgressBars::default();
 
         let release = gd::find_release_with_asset(
             &None,
@@ -86,6 +137,7 @@ impl RunCommand for UpdateVersionCommand {
             &current.name_parts.platform,
             &current.name_parts.architecture,
             &current.name_parts.flavour,
+            &channel,
             &client,
         )
         .await?;
@@ -93,21 +145,30 @@ impl RunCommand for UpdateVersionCommand {
         let asset = release.assets.first().unwrap();
         let version_name = asset.name.trim_end_matches(".zip");
 
-        if gdman::activate_by_name_if_installed(version_name)? {
-            if self.uninstall {
-                gdman::uninstall_version(&current)?;
-            }
-            return Ok(());
+        if !gdman::activate_by_name_if_installed(version_name)? {
+            gdman::install_release(&release, &client, self.skip_verify, &progress).await?;
+            gdman::set_active_godot_version(version_name)?;
         }
 
-        gdman::download_godot_version(version_name, &client, &asset.browser_download_url).await?;
-
-        gdman::set_active_godot_version(version_name)?;
+        if with_templates {
+            let updated = gdman::get_current_version()?;
+            gdman::install_templates_if_missing(
+                &updated.name_parts,
+                &client,
+                self.skip_verify,
+                &progress,
+            )
+            .await?;
+        }
 
         if self.uninstall {
             gdman::uninstall_version(&current)?;
         }
 
+        if let Some(keep) = keep {
+            gdman::uninstall_old_versions(keep)?;
+        }
+
         return Ok(());
     }
 }
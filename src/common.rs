@@ -1,8 +1,10 @@
 use std::env;
 use strum_macros::{Display, EnumString, VariantNames};
 
+use crate::error::GdmanError;
+
 pub trait FromOS<T> {
-    fn from_os() -> Result<T, String>;
+    fn from_os() -> Result<T, GdmanError>;
 }
 
 #[derive(Clone, Debug, PartialEq, Display)]
@@ -13,12 +15,15 @@ pub enum Platform {
 }
 
 impl FromOS<Platform> for Platform {
-    fn from_os() -> Result<Platform, String> {
+    fn from_os() -> Result<Platform, GdmanError> {
         match env::consts::OS {
             "linux" => Ok(Platform::Linux),
             "windows" => Ok(Platform::Windows),
             "macos" => Ok(Platform::MacOS),
-            _ => Err(format!("Unsupported platform {}", env::consts::OS)),
+            _ => Err(GdmanError::InvalidInput(format!(
+                "Unsupported platform {}",
+                env::consts::OS
+            ))),
         }
     }
 }
@@ -30,6 +35,28 @@ pub enum Flavour {
     Mono,
 }
 
+/// The stability of a Godot release, ordered from least to most stable
+#[derive(
+    EnumString,
+    VariantNames,
+    clap::ValueEnum,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Display,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Channel {
+    Dev,
+    Beta,
+    Rc,
+    Stable,
+}
+
 #[derive(EnumString, VariantNames, clap::ValueEnum, Clone, Debug, PartialEq, Display)]
 #[strum(serialize_all = "lowercase")]
 pub enum Architecture {
@@ -46,7 +73,7 @@ pub enum Architecture {
 }
 
 impl FromOS<Architecture> for Architecture {
-    fn from_os() -> Result<Architecture, String> {
+    fn from_os() -> Result<Architecture, GdmanError> {
         #[cfg(target_os = "macos")]
         if Platform::from_os().unwrap() == Platform::MacOS {
             return Ok(Architecture::Universal);
@@ -62,7 +89,10 @@ impl FromOS<Architecture> for Architecture {
             "x86" => Ok(Architecture::X86),
             #[cfg(any(unix, windows))]
             "x86_64" => Ok(Architecture::X64),
-            _ => Err("Unsupported architecture".to_owned()),
+            _ => Err(GdmanError::InvalidInput(format!(
+                "Unsupported architecture {}",
+                env::consts::ARCH
+            ))),
         }
     }
 }
@@ -0,0 +1,263 @@
+/*
+    User configuration, read from a TOML file in the user's config directory.
+    Each setting can also be set via an environment variable, named after the
+    setting, e.g. GDMAN_CACHE_TTL for cache-ttl. Settings are resolved in order of
+    precedence: CLI arg > environment variable > project file > config file > default
+*/
+
+use std::{env, fs, num::NonZeroU32, path::PathBuf, str::FromStr, sync::OnceLock};
+
+use strum_macros::{Display, EnumString, VariantNames};
+
+use crate::{
+    common::{Architecture, Channel, Flavour},
+    error::GdmanError,
+    mirrors::Mirrors,
+    release_source::SourceKind,
+};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+const CONFIG_ENV_VAR: &str = "GDMAN_CONFIG";
+
+static CONFIG: OnceLock<toml::Table> = OnceLock::new();
+
+#[derive(EnumString, VariantNames, clap::ValueEnum, Clone, Copy, Debug, PartialEq, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ConfigKey {
+    /// The flavour of Godot to install when none is specified
+    Flavour,
+    /// The architecture to install when none is specified
+    Architecture,
+    /// The least stable channel of release to install when none is specified
+    Channel,
+    /// How long (in seconds) releases fetched from GitHub are cached for
+    CacheTtl,
+    /// Whether to install the export templates along with each version
+    WithTemplates,
+    /// How many versions to keep installed, the oldest are uninstalled after installing a new one
+    Keep,
+    /// How many downloads to run at once, e.g. a version along with its export templates
+    Parallelism,
+    /// The directory gdman keeps its data in
+    Home,
+    /// The directory versions of Godot are installed to
+    VersionsDir,
+    /// The directory the godot link is created in
+    LinkDir,
+    /// How long (in seconds) to wait for another gdman process to finish before giving up
+    LockTimeout,
+    /// Where releases are found and downloaded from, being github, http-index or local
+    ReleaseSource,
+    /// The URL of the http-index release source, or the directory of the local release source
+    ReleaseSourceUrl,
+    /// The mirrors to try downloading from before the release source, in order and comma-separated
+    Mirrors,
+}
+
+impl ConfigKey {
+    pub fn env_var(&self) -> String {
+        return format!(
+            "GDMAN_{}",
+            self.to_string().to_uppercase().replace('-', "_")
+        );
+    }
+
+    /// Checks the value is valid for the setting, returning it as the TOML value to store
+    fn parse_value(&self, value: &str) -> Result<toml::Value, GdmanError> {
+        let invalid =
+            |e: String| GdmanError::InvalidInput(format!("Invalid value {value} for {self}\n{e}"));
+        return match self {
+            ConfigKey::Flavour => Flavour::from_str(value)
+                .map(|_| toml::Value::String(value.to_owned()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::Architecture => Architecture::from_str(value)
+                .map(|_| toml::Value::String(value.to_owned()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::Channel => Channel::from_str(value)
+                .map(|_| toml::Value::String(value.to_owned()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::CacheTtl | ConfigKey::Keep | ConfigKey::LockTimeout => value
+                .parse::<u32>()
+                .map(|v| toml::Value::Integer(v.into()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::Parallelism => value
+                .parse::<NonZeroU32>()
+                .map(|v| toml::Value::Integer(v.get().into()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::WithTemplates => value
+                .parse::<bool>()
+                .map(toml::Value::Boolean)
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::ReleaseSource => SourceKind::from_str(value)
+                .map(|_| toml::Value::String(value.to_owned()))
+                .map_err(|e| invalid(e.to_string())),
+            ConfigKey::Mirrors => Mirrors::from_str(value)
+                .map(|_| toml::Value::String(value.to_owned()))
+                .map_err(invalid),
+            ConfigKey::Home
+            | ConfigKey::VersionsDir
+            | ConfigKey::LinkDir
+            | ConfigKey::ReleaseSourceUrl => Ok(toml::Value::String(value.to_owned())),
+        };
+    }
+}
+
+/// Loads the config file, if there is one. This needs to happen before any settings are resolved.
+pub fn init() -> Result<(), GdmanError> {
+    let path = get_config_file_path()?;
+
+    let config = match fs::read_to_string(&path) {
+        Err(_) => {
+            log::trace!("No config file at {}", path.display());
+            toml::Table::new()
+        }
+        Ok(content) => match toml::from_str::<toml::Table>(&content) {
+            Err(e) => {
+                return Err(GdmanError::parse(
+                    format!("Invalid config file {}", path.display()),
+                    e,
+                ))
+            }
+            Ok(c) => {
+                log::trace!("Loaded config file {}", path.display());
+                c
+            }
+        },
+    };
+
+    for key in config.keys() {
+        if ConfigKey::from_str(key).is_err() {
+            log::warn!("Ignoring unknown setting {key} in {}", path.display());
+        }
+    }
+
+    let _ = CONFIG.set(config);
+
+    return Ok(());
+}
+
+/// Resolves a setting from the CLI arg, environment variable, project file,
+/// then config file, in that order. Returns None if it's not set anywhere.
+pub fn resolve<T: FromStr>(
+    key: ConfigKey,
+    cli: Option<T>,
+    project: Option<T>,
+) -> Result<Option<T>, GdmanError> {
+    if cli.is_some() {
+        return Ok(cli);
+    }
+
+    let var = key.env_var();
+    if let Some(value) = env::var(&var).ok().filter(|v| !v.is_empty()) {
+        return match T::from_str(&value) {
+            Err(_) => Err(GdmanError::InvalidInput(format!(
+                "Invalid value {value} for {var}"
+            ))),
+            Ok(v) => Ok(Some(v)),
+        };
+    }
+
+    if project.is_some() {
+        return Ok(project);
+    }
+
+    return match get(key) {
+        None => Ok(None),
+        Some(value) => match T::from_str(&value) {
+            Err(_) => Err(GdmanError::InvalidInput(format!(
+                "Invalid value {value} for {key} in config file"
+            ))),
+            Ok(v) => Ok(Some(v)),
+        },
+    };
+}
+
+/// Gets the value of a setting from the config file
+pub fn get(key: ConfigKey) -> Option<String> {
+    return CONFIG
+        .get_or_init(toml::Table::new)
+        .get(&key.to_string())
+        .map(|v| match v {
+            toml::Value::String(s) => s.clone(),
+            v => v.to_string(),
+        });
+}
+
+/// Sets the value of a setting in the config file, or removes it if no value is specified
+pub fn set(key: ConfigKey, value: Option<&str>) -> Result<(), GdmanError> {
+    let path = get_config_file_path()?;
+
+    let mut config = CONFIG.get_or_init(toml::Table::new).clone();
+
+    match value {
+        None => {
+            config.remove(&key.to_string());
+        }
+        Some(value) => {
+            config.insert(key.to_string(), key.parse_value(value)?);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            return Err(GdmanError::io(
+                format!("Failed to create config directory {}", parent.display()),
+                e,
+            ));
+        }
+    }
+
+    let content = match toml::to_string(&config) {
+        Err(e) => return Err(GdmanError::Other(format!("Error serializing config\n{e}"))),
+        Ok(c) => c,
+    };
+
+    log::trace!("Saving config to {}", path.display());
+
+    return fs::write(&path, content)
+        .map_err(|e| GdmanError::io(format!("Failed to write config file {}", path.display()), e));
+}
+
+/// The config file is GDMAN_CONFIG if set, otherwise config.toml in the gdman config directory
+pub fn get_config_file_path() -> Result<PathBuf, GdmanError> {
+    if let Some(path) = env::var_os(CONFIG_ENV_VAR).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    return Ok(get_config_dir()?.join("gdman").join(CONFIG_FILE_NAME));
+}
+
+/// On linux, config is stored under $XDG_CONFIG_HOME, which defaults to ~/.config
+#[cfg(target_os = "linux")]
+fn get_config_dir() -> Result<PathBuf, GdmanError> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    return match env::var_os("HOME") {
+        None => Err(GdmanError::Other(
+            "Cant determine config directory, HOME is not set".to_owned(),
+        )),
+        Some(home) => Ok(PathBuf::from(home).join(".config")),
+    };
+}
+
+/// On Windows, config is stored under %APPDATA%
+#[cfg(windows)]
+fn get_config_dir() -> Result<PathBuf, GdmanError> {
+    return match env::var_os("APPDATA") {
+        None => Err(GdmanError::Other(
+            "Cant determine config directory, APPDATA is not set".to_owned(),
+        )),
+        Some(app_data) => Ok(PathBuf::from(app_data)),
+    };
+}
+
+/// On MacOS, config is stored under ~/Library/Application Support
+#[cfg(target_os = "macos")]
+fn get_config_dir() -> Result<PathBuf, GdmanError> {
+    return match env::var_os("HOME") {
+        None => Err(GdmanError::Other(
+            "Cant determine config directory, HOME is not set".to_owned(),
+        )),
+        Some(home) => Ok(PathBuf::from(home).join("Library/Application Support")),
+    };
+}
@@ -0,0 +1,236 @@
+/*
+    Custom builds of Godot, e.g. those compiled from source with engine patches,
+    registered with gdman via the link-custom command. gdman only keeps a record of
+    where each build is and which version it's based on, in custom-versions.toml in
+    the home directory, so the builds themselves are never moved or deleted.
+*/
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::{Architecture, Flavour, FromOS, Platform},
+    error::GdmanError,
+    gd_semver::parse_semver_version,
+    gdman::{self, GodotVersionInfo},
+    github::godot_repo::GodotVersionNameParts,
+};
+
+const CUSTOM_VERSIONS_FILE_NAME: &str = "custom-versions.toml";
+
+#[derive(Serialize, Deserialize)]
+struct CustomVersionToml {
+    path: PathBuf,
+    version: String,
+    flavour: String,
+    architecture: String,
+}
+
+/// A custom build registered with gdman
+#[derive(Clone)]
+pub struct CustomVersion {
+    pub exe_path: PathBuf,
+    pub name_parts: GodotVersionNameParts,
+}
+
+impl CustomVersion {
+    /// The custom build as an installed version, whose path is the executable since there's
+    /// no telling what else is in the directory it's in
+    pub fn to_version_info(&self) -> GodotVersionInfo {
+        return GodotVersionInfo {
+            path: self.exe_path.clone(),
+            name_parts: self.name_parts.clone(),
+            custom: true,
+        };
+    }
+}
+
+/// Gets the custom builds registered with gdman, in order of name
+pub fn get_custom_versions() -> Result<Vec<CustomVersion>, GdmanError> {
+    return load()?
+        .iter()
+        .map(|(name, custom)| to_custom_version(name, custom))
+        .collect();
+}
+
+pub fn find_custom_version(name: &str) -> Result<Option<CustomVersion>, GdmanError> {
+    return Ok(get_custom_versions()?
+        .into_iter()
+        .find(|c| c.name_parts.version_name == name));
+}
+
+/// Finds the custom build with the executable, e.g. the target of the godot link
+pub fn find_custom_version_by_exe(exe_path: &Path) -> Result<Option<CustomVersion>, GdmanError> {
+    // The paths of the executables are stored canonicalized
+    let exe_path = exe_path.canonicalize().unwrap_or(exe_path.to_path_buf());
+    return Ok(get_custom_versions()?
+        .into_iter()
+        .find(|c| c.exe_path == exe_path));
+}
+
+/// Registers the custom build at the path, being either the Godot executable or a directory
+/// containing it (laid out like the versions gdman installs), under the name. The version, flavour
+/// and architecture are those the build is based on. If a custom build is already registered
+/// under the name, it's replaced.
+pub fn link_custom_version(
+    name: &str,
+    path: &Path,
+    version: &str,
+    flavour: &Flavour,
+    architecture: &Architecture,
+) -> Result<CustomVersion, GdmanError> {
+    if name.is_empty() || name.starts_with('.') {
+        return Err(GdmanError::InvalidInput(format!(
+            "Invalid name {name} for a custom build"
+        )));
+    }
+
+    // The names need to be unique, since versions are activated by name
+    if gdman::read_versions_dir()?
+        .versions
+        .iter()
+        .any(|v| v.name_parts.version_name == name)
+    {
+        return Err(GdmanError::InvalidInput(format!(
+            "{name} is already the name of an installed version"
+        )));
+    }
+
+    parse_semver_version(version, &Some(vec!["stable".to_owned()]))?;
+
+    let exe_path = find_exe(path)?;
+
+    let mut custom_versions = load()?;
+    let custom = CustomVersionToml {
+        path: exe_path,
+        version: version.to_owned(),
+        flavour: flavour.to_string(),
+        architecture: architecture.to_string(),
+    };
+    let custom_version = to_custom_version(name, &custom)?;
+
+    if custom_versions.insert(name.to_owned(), custom).is_some() {
+        log::trace!("Replacing custom build {name}");
+    }
+    save(&custom_versions)?;
+
+    return Ok(custom_version);
+}
+
+/// Removes the custom build from gdman's records, leaving the build itself in place
+pub fn unlink_custom_version(name: &str) -> Result<(), GdmanError> {
+    let mut custom_versions = load()?;
+    if custom_versions.remove(name).is_none() {
+        return Err(GdmanError::NotInstalled(name.to_owned()));
+    }
+    return save(&custom_versions);
+}
+
+pub fn get_custom_versions_file_path() -> Result<PathBuf, GdmanError> {
+    return Ok(gdman::get_home_dir()?.join(CUSTOM_VERSIONS_FILE_NAME));
+}
+
+/// Gets the absolute path of the executable, looking for it if the path is a directory
+fn find_exe(path: &Path) -> Result<PathBuf, GdmanError> {
+    let path = match path.canonicalize() {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to resolve {}", path.display()),
+                e,
+            ))
+        }
+        Ok(p) => p,
+    };
+
+    if path.is_file() {
+        return Ok(path);
+    }
+
+    return gdman::get_godot_exe_path(&path).map_err(|_| {
+        GdmanError::InvalidInput(format!(
+            "Couldn't find the Godot executable in {}, specify the path of the executable instead",
+            path.display()
+        ))
+    });
+}
+
+fn to_custom_version(name: &str, custom: &CustomVersionToml) -> Result<CustomVersion, GdmanError> {
+    let invalid = |what: &str, value: &str| {
+        GdmanError::InvalidInput(format!("Invalid {what} {value} for custom build {name}"))
+    };
+
+    let version = match parse_semver_version(&custom.version, &Some(vec!["stable".to_owned()])) {
+        Err(_) => return Err(invalid("version", &custom.version)),
+        Ok(v) => v,
+    };
+    let flavour = match Flavour::from_str(&custom.flavour) {
+        Err(_) => return Err(invalid("flavour", &custom.flavour)),
+        Ok(f) => f,
+    };
+    let architecture = match Architecture::from_str(&custom.architecture) {
+        Err(_) => return Err(invalid("architecture", &custom.architecture)),
+        Ok(a) => a,
+    };
+
+    return Ok(CustomVersion {
+        exe_path: custom.path.clone(),
+        name_parts: GodotVersionNameParts {
+            version_string: custom.version.clone(),
+            version,
+            version_name: name.to_owned(),
+            platform: Platform::from_os()?,
+            architecture,
+            flavour,
+        },
+    });
+}
+
+fn load() -> Result<BTreeMap<String, CustomVersionToml>, GdmanError> {
+    let path = get_custom_versions_file_path()?;
+
+    let content = match fs::read_to_string(&path) {
+        Err(_) => {
+            log::trace!("No custom builds at {}", path.display());
+            return Ok(BTreeMap::new());
+        }
+        Ok(c) => c,
+    };
+
+    return match toml::from_str(&content) {
+        Err(e) => Err(GdmanError::parse(format!("Invalid {}", path.display()), e)),
+        Ok(c) => Ok(c),
+    };
+}
+
+fn save(custom_versions: &BTreeMap<String, CustomVersionToml>) -> Result<(), GdmanError> {
+    let path = get_custom_versions_file_path()?;
+
+    if let Some(parent) = path.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            return Err(GdmanError::io(
+                format!("Failed to create directory {}", parent.display()),
+                e,
+            ));
+        }
+    }
+
+    let content = match toml::to_string(custom_versions) {
+        Err(e) => {
+            return Err(GdmanError::Other(format!(
+                "Error serializing custom builds\n{e}"
+            )))
+        }
+        Ok(c) => c,
+    };
+
+    log::trace!("Saving custom builds to {}", path.display());
+
+    return fs::write(&path, content)
+        .map_err(|e| GdmanError::io(format!("Failed to write {}", path.display()), e));
+}
@@ -0,0 +1,242 @@
+/*
+    The errors that gdman can fail with. Each error's message only describes
+    the error itself, with the underlying error (if any) available via source(),
+    so use report() to get the messages of the whole chain.
+    Each kind of error exits with its own code, so scripts can tell them apart.
+*/
+
+use std::{error::Error, fmt, io, path::PathBuf};
+
+use chrono::{DateTime, Local};
+use reqwest::StatusCode;
+
+/// Something went wrong that isn't covered by one of the other exit codes
+pub const EXIT_FAILURE: u8 = 1;
+/// The arguments, config or project file are invalid (clap uses this for invalid arguments too)
+pub const EXIT_INVALID_INPUT: u8 = 2;
+/// The requested version isn't installed, no release matches it, or no version is active
+pub const EXIT_NOT_FOUND: u8 = 3;
+/// A request failed, e.g. due to no network connection or a server error
+pub const EXIT_NETWORK: u8 = 4;
+/// The GitHub API rate limit has been exceeded
+pub const EXIT_RATE_LIMITED: u8 = 5;
+/// A download couldn't be verified against the checksums published with the release
+pub const EXIT_CHECKSUM: u8 = 6;
+/// Something needs to be downloaded, but gdman is running offline
+pub const EXIT_OFFLINE: u8 = 7;
+/// Another gdman process held the lock for longer than the lock-timeout setting
+pub const EXIT_LOCK_TIMEOUT: u8 = 8;
+
+#[derive(Debug)]
+pub enum GdmanError {
+    /// Reading or writing a file or directory failed
+    Io { context: String, source: io::Error },
+    /// A request couldn't be sent or the response couldn't be read, e.g. no network connection
+    Network { url: String, source: reqwest::Error },
+    /// The server responded to a request with an error
+    Http {
+        url: String,
+        status: StatusCode,
+        message: Option<String>,
+    },
+    /// The GitHub API refused a request because too many have been made
+    RateLimited {
+        resets_at: Option<DateTime<Local>>,
+        /// The environment variable to set a token in, if one isn't set already
+        token_env_var: Option<&'static str>,
+    },
+    /// A download kept failing, even after retrying
+    DownloadFailed {
+        attempts: u32,
+        source: Box<GdmanError>,
+    },
+    /// A downloaded file doesn't match the checksum published for it
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// The checksum of a download can't be found, so it can't be verified
+    ChecksumUnavailable(String),
+    /// An archive couldn't be extracted, or contains something that shouldn't be extracted
+    InvalidArchive(String),
+    /// A version directory doesn't contain the Godot executable
+    ExecutableNotFound(PathBuf),
+    /// The version isn't installed
+    NotInstalled(String),
+    /// No version is active, because the godot link doesn't exist
+    NoActiveVersion(PathBuf),
+    /// No release matches the requested version
+    VersionNotFound(String),
+    /// The godot link exists but its target can't be determined
+    BrokenLink { path: PathBuf, reason: String },
+    /// A directory name isn't the name of a version of Godot
+    InvalidVersionName { name: String, reason: String },
+    /// A file or response couldn't be parsed
+    Parse {
+        context: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// An argument, setting or project file is invalid
+    InvalidInput(String),
+    /// The operation needs to download something, but gdman is offline
+    Offline(String),
+    /// Another gdman process held the lock for too long
+    LockTimeout { seconds: u64, owner: Option<String> },
+    /// Anything else, which scripts aren't expected to handle differently
+    Other(String),
+}
+
+impl GdmanError {
+    pub fn io(context: impl Into<String>, source: io::Error) -> GdmanError {
+        return GdmanError::Io {
+            context: context.into(),
+            source,
+        };
+    }
+
+    pub fn parse(
+        context: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> GdmanError {
+        return GdmanError::Parse {
+            context: context.into(),
+            source: source.into(),
+        };
+    }
+
+    /// The code the process exits with when failing with this error
+    pub fn exit_code(&self) -> u8 {
+        return match self {
+            GdmanError::InvalidInput(_) => EXIT_INVALID_INPUT,
+            GdmanError::NotInstalled(_)
+            | GdmanError::NoActiveVersion(_)
+            | GdmanError::VersionNotFound(_) => EXIT_NOT_FOUND,
+            GdmanError::Network { .. } | GdmanError::Http { .. } => EXIT_NETWORK,
+            GdmanError::DownloadFailed { source, .. } => source.exit_code(),
+            GdmanError::RateLimited { .. } => EXIT_RATE_LIMITED,
+            GdmanError::ChecksumMismatch { .. } | GdmanError::ChecksumUnavailable(_) => {
+                EXIT_CHECKSUM
+            }
+            GdmanError::Offline(_) => EXIT_OFFLINE,
+            GdmanError::LockTimeout { .. } => EXIT_LOCK_TIMEOUT,
+            GdmanError::Io { .. }
+            | GdmanError::InvalidArchive(_)
+            | GdmanError::ExecutableNotFound(_)
+            | GdmanError::BrokenLink { .. }
+            | GdmanError::InvalidVersionName { .. }
+            | GdmanError::Parse { .. }
+            | GdmanError::Other(_) => EXIT_FAILURE,
+        };
+    }
+
+    /// The error's message followed by the messages of each of its sources, one per line
+    pub fn report(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = self.source();
+        while let Some(e) = source {
+            message.push('\n');
+            message.push_str(&e.to_string());
+            source = e.source();
+        }
+        return message;
+    }
+}
+
+impl fmt::Display for GdmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            GdmanError::Io { context, .. } => write!(f, "{context}"),
+            GdmanError::Network { url, .. } => write!(f, "Request to {url} failed"),
+            GdmanError::Http {
+                url,
+                status,
+                message,
+            } => {
+                write!(f, "Request to {url} failed with status {status}")?;
+                match message {
+                    None => Ok(()),
+                    Some(m) => write!(f, "\n{m}"),
+                }
+            }
+            GdmanError::RateLimited {
+                resets_at,
+                token_env_var,
+            } => {
+                write!(f, "GitHub API rate limit exceeded")?;
+                if let Some(resets_at) = resets_at {
+                    write!(
+                        f,
+                        ", it resets at {}",
+                        resets_at.format("%Y-%m-%d %H:%M:%S")
+                    )?;
+                }
+                match token_env_var {
+                    None => Ok(()),
+                    Some(var) => write!(
+                        f,
+                        "\nSet the {var} environment variable to a GitHub token for a higher rate limit"
+                    ),
+                }
+            }
+            GdmanError::DownloadFailed { attempts, .. } => write!(
+                f,
+                "Gave up after {attempts} attempts, run the command again to resume the download"
+            ),
+            GdmanError::ChecksumMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {name}\nExpected {expected}\nActual {actual}"
+            ),
+            GdmanError::ChecksumUnavailable(message) => write!(f, "{message}"),
+            GdmanError::InvalidArchive(message) => write!(f, "{message}"),
+            GdmanError::ExecutableNotFound(dir) => {
+                write!(f, "Cant find Godot executable in {}", dir.display())
+            }
+            GdmanError::NotInstalled(name) => write!(f, "{name} is not installed"),
+            GdmanError::NoActiveVersion(link_path) => write!(
+                f,
+                "Cant determine current version, godot link not found at {}",
+                link_path.display()
+            ),
+            GdmanError::VersionNotFound(message) => write!(f, "{message}"),
+            GdmanError::BrokenLink { path, reason } => write!(
+                f,
+                "Cant determine current version, godot link {} may be broken\n{reason}\nRun gdman doctor to diagnose the problem",
+                path.display()
+            ),
+            GdmanError::InvalidVersionName { name, reason } => {
+                write!(f, "{name} is not a recognized version of Godot\n{reason}")
+            }
+            GdmanError::Parse { context, .. } => write!(f, "{context}"),
+            GdmanError::InvalidInput(message) => write!(f, "{message}"),
+            GdmanError::Offline(message) => write!(f, "{message}"),
+            GdmanError::LockTimeout { seconds, owner } => {
+                write!(
+                    f,
+                    "Timed out after {seconds} seconds waiting for another gdman process"
+                )?;
+                match owner {
+                    None => Ok(()),
+                    Some(pid) => write!(f, " (pid {pid})"),
+                }
+            }
+            GdmanError::Other(message) => write!(f, "{message}"),
+        };
+    }
+}
+
+impl Error for GdmanError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            GdmanError::Io { source, .. } => Some(source),
+            GdmanError::Network { source, .. } => Some(source),
+            GdmanError::DownloadFailed { source, .. } => Some(source.as_ref()),
+            GdmanError::Parse { source, .. } => Some(source.as_ref()),
+            _ => None,
+        };
+    }
+}
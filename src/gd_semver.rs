@@ -4,11 +4,13 @@
     rust semver uses.
 */
 
-use std::str::FromStr;
+use std::{cmp::Ordering, str::FromStr};
 
 use regex::Regex;
 use semver::VersionReq;
 
+use crate::{common::Channel, error::GdmanError};
+
 const VERSION_REGEX: &str = r"^(?<major>0|[1-9]\d*)(\.(?<minor>0|[1-9]\d*))?(\.(?<patch>0|[1-9]\d*))?(?:-(?<pre>(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+(?<meta>[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$";
 
 #[derive(Clone, Debug)]
@@ -35,7 +37,16 @@ impl FromStr for MaybeVersionOrVersionReq {
                     input_str: s.to_string(),
                 }),
             },
-            Err(_) => Err(format!("Invalid version: {s}").to_owned()),
+            // Godot pre-release versions omit the patch when it's zero, e.g. 4.4-beta2,
+            // which semver doesn't accept as a requirement, but we can parse it as an exact version
+            Err(_) => match parse_semver_version(s, &Some(vec!["stable".to_owned()])) {
+                Err(_) => Err(format!("Invalid version: {s}").to_owned()),
+                Ok(v) => Ok(MaybeVersionOrVersionReq {
+                    version_like: VersionReq::parse(&format!("^{v}")).unwrap(),
+                    version_exact: Some(v),
+                    input_str: s.to_string(),
+                }),
+            },
         };
     }
 }
@@ -43,17 +54,16 @@ impl FromStr for MaybeVersionOrVersionReq {
 pub fn parse_semver_version(
     value: &str,
     ignored_pre_releases: &Option<Vec<String>>,
-) -> Result<semver::Version, String> {
-    let reg = match Regex::new(&VERSION_REGEX) {
-        Err(e) => return Err(e.to_string().to_owned()),
+) -> Result<semver::Version, GdmanError> {
+    let reg = match Regex::new(VERSION_REGEX) {
+        Err(e) => return Err(GdmanError::Other(e.to_string())),
         Ok(r) => r,
     };
 
-    if !reg.is_match(&value) {
-        return Err(format!("Invalid version {value}"));
-    }
-
-    let captures = reg.captures(value).unwrap();
+    let captures = match reg.captures(value) {
+        None => return Err(GdmanError::InvalidInput(format!("Invalid version {value}"))),
+        Some(c) => c,
+    };
     // major is required
     let maj = match_or_default(value, captures.name("major"), 0);
     // minor is optional for godot but required by semver - default it to zero
@@ -78,11 +88,59 @@ pub fn parse_semver_version(
         }
     }
     return match semver::Version::parse(&version_str) {
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(GdmanError::parse(format!("Invalid version {value}"), e)),
         Ok(v) => Ok(v),
     };
 }
 
+/// Gets the channel of the version from it's pre-release, e.g. 4.4.0-beta2 is a beta
+pub fn get_channel(version: &semver::Version) -> Channel {
+    return parse_pre_release(&version.pre).0;
+}
+
+/// Compares versions the way Godot orders them. Pre-releases of the same version are
+/// ordered dev < beta < rc < stable, and their numbers are compared numerically, so
+/// beta10 is later than beta2 (semver would compare them alphabetically).
+pub fn compare_versions(a: &semver::Version, b: &semver::Version) -> Ordering {
+    return (a.major, a.minor, a.patch, parse_pre_release(&a.pre)).cmp(&(
+        b.major,
+        b.minor,
+        b.patch,
+        parse_pre_release(&b.pre),
+    ));
+}
+
+/// Checks if the version satisfies the requirement, or would satisfy it if it were stable.
+/// This allows pre-releases to match requirements such as ~4.4 when they're being looked for.
+pub fn matches_including_pre_releases(
+    version_like: &semver::VersionReq,
+    version: &semver::Version,
+) -> bool {
+    let mut stable_version = version.clone();
+    stable_version.pre = semver::Prerelease::EMPTY;
+    return version_like.matches(version) || version_like.matches(&stable_version);
+}
+
+/// Splits a Godot pre-release, e.g. beta2, into its channel and number.
+/// Anything less stable than a beta (e.g. dev or alpha) is considered a dev release.
+fn parse_pre_release(pre: &semver::Prerelease) -> (Channel, u64) {
+    if pre.is_empty() {
+        return (Channel::Stable, 0);
+    }
+
+    let pre = pre.as_str();
+    let (name, number) = pre.split_at(pre.find(|c: char| c.is_ascii_digit()).unwrap_or(pre.len()));
+
+    let channel = match name.trim_end_matches('.') {
+        "stable" => Channel::Stable,
+        "rc" => Channel::Rc,
+        "beta" => Channel::Beta,
+        _ => Channel::Dev,
+    };
+
+    return (channel, number.parse().unwrap_or(0));
+}
+
 fn match_or_default<T: FromStr>(input: &str, r_match: Option<regex::Match>, default: T) -> T
 where
     <T as FromStr>::Err: std::fmt::Debug,
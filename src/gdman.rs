@@ -1,52 +1,112 @@
 use std::{
+    collections::HashSet,
     env,
     fs::{self, remove_file, DirEntry},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    process::Command,
+    sync::Once,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use async_zip::tokio::read::seek::ZipFileReader;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::{header, Url};
+use futures_lite::AsyncReadExt as _;
+use indicatif::HumanBytes;
+use reqwest::{header, StatusCode, Url};
+use sha2::{Digest, Sha512};
 use tokio::{
     fs::{create_dir_all, File, OpenOptions},
-    io::{AsyncWriteExt, BufReader},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader},
 };
 use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
 use crate::{
-    common::{Architecture, Flavour, Platform},
-    github::godot_repo::{self as gd, parse_version_name, GodotVersionNameParts},
+    common::{Architecture, Channel, Flavour, Platform},
+    config::{self, ConfigKey},
+    custom,
+    error::GdmanError,
+    gd_semver::{
+        compare_versions, flatten_version, parse_semver_version, MaybeVersionOrVersionReq,
+    },
+    github::{
+        github_repo as gh,
+        godot_repo::{self as gd, parse_version_name, GodotVersionNameParts},
+        releases_cache,
+    },
+    lock, mirrors,
+    progress::{ProgressEvent, ProgressReporter},
+    release_source::{self, ReleaseSource},
 };
 
-pub fn set_active_godot_version(version_name: &str) -> Result<(), String> {
+const GODOT_3_TEMPLATES_DIR_NAME: &str = "templates";
+const GODOT_4_TEMPLATES_DIR_NAME: &str = "export_templates";
+const STAGING_DIR_NAME: &str = ".staging";
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+/// The most that will be extracted from an archive, to guard against zip bombs
+const MAX_EXTRACTED_SIZE: u64 = 8 * 1024 * 1024 * 1024;
+const MAX_SYMLINK_TARGET_LENGTH: u64 = 4096;
+/// The bits of a unix mode that specify the type of file, and the type for a symlink
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+#[cfg(unix)]
+const DEFAULT_FILE_MODE: u32 = 0o644;
+#[cfg(unix)]
+const EXECUTABLE_FILE_MODE: u32 = 0o755;
+const DOWNLOAD_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+static WARN_UNRECOGNIZED: Once = Once::new();
+
+pub fn set_active_godot_version(version_name: &str) -> Result<(), GdmanError> {
     log::trace!("Setting active Godot version to {version_name}");
 
-    let link_path = get_godot_link_path()?;
+    return link_godot_version(&get_godot_link_path()?, version_name);
+}
 
-    remove_link(&link_path)?;
+/// Points the godot link at the path to the version's executable
+fn link_godot_version(link_path: &PathBuf, version_name: &str) -> Result<(), GdmanError> {
+    create_link_dir(link_path)?;
+    remove_link(link_path)?;
 
-    let version_dir_path = get_versions_dir()?;
-    let target_version_dir = version_dir_path.join(version_name);
+    let target_exe_path = match custom::find_custom_version(version_name)? {
+        Some(custom) => {
+            if !custom.exe_path.is_file() {
+                return Err(GdmanError::ExecutableNotFound(custom.exe_path));
+            }
+            custom.exe_path
+        }
+        None => {
+            let target_version_dir = get_versions_dir()?.join(version_name);
 
-    if !target_version_dir.is_dir() {
-        return Err(format!(
-            "Version directory {} does not exist",
-            target_version_dir.display()
-        ));
-    }
+            if !target_version_dir.is_dir() {
+                return Err(GdmanError::NotInstalled(version_name.to_owned()));
+            }
 
-    let target_exe_path = get_godot_exe_path(&target_version_dir)?;
+            get_godot_exe_path(&target_version_dir)?
+        }
+    };
 
     log::trace!("Linking godot command to {}", target_exe_path.display());
 
-    create_link(&link_path, &target_exe_path)?;
+    create_link(link_path, &target_exe_path)?;
 
     log::info!("Set {version_name} active");
 
     return Ok(());
 }
 
+/// Gets the path of the version's Godot executable, which for a custom build is wherever it was linked from
+pub fn get_version_exe_path(version: &GodotVersionInfo) -> Result<PathBuf, GdmanError> {
+    if version.custom {
+        return Ok(version.path.clone());
+    }
+    return get_godot_exe_path(&version.path);
+}
+
 pub fn already_installed(version_name: &str) -> bool {
+    if let Ok(Some(custom)) = custom::find_custom_version(version_name) {
+        log::trace!("{version_name} is a custom build");
+        return custom.exe_path.is_file();
+    }
+
     let mut dir_path = match get_versions_dir() {
         Err(_) => return false,
         Ok(d) => d,
@@ -73,73 +133,962 @@ pub fn already_installed(version_name: &str) -> bool {
     };
 }
 
-pub fn get_versions_dir() -> Result<PathBuf, String> {
-    let mut dir = get_base_dir()?;
-    dir.push("versions");
+/// The directory the versions of Godot are installed to.
+/// This is the versions-dir setting if set, otherwise the versions directory within the gdman home directory.
+pub fn get_versions_dir() -> Result<PathBuf, GdmanError> {
+    let dir = match config::resolve::<PathBuf>(ConfigKey::VersionsDir, None, None)? {
+        Some(d) => d,
+        None => get_home_dir()?.join("versions"),
+    };
     return match fs::create_dir_all(&dir) {
-        Err(e) => Err(format!(
-            "versions directory does not exist and failed to create it\n{}\n{}",
-            dir.display(),
-            e
+        Err(e) => Err(GdmanError::io(
+            format!(
+                "versions directory does not exist and failed to create it\n{}",
+                dir.display()
+            ),
+            e,
         )),
         Ok(_) => Ok(dir),
     };
 }
 
-pub fn get_base_dir() -> Result<PathBuf, String> {
-    match env::current_exe() {
-        Ok(dir) => Ok(dir.parent().unwrap().to_owned()),
-        Err(e) => Err(e.to_string()),
+/// The directory gdman keeps its data in. This is the home setting (e.g. GDMAN_HOME) if set,
+/// otherwise a gdman directory within the user's data directory, e.g. ~/.local/share/gdman
+pub fn get_home_dir() -> Result<PathBuf, GdmanError> {
+    if let Some(dir) = config::resolve::<PathBuf>(ConfigKey::Home, None, None)? {
+        return Ok(dir);
     }
+    return Ok(get_user_data_dir()?.join("gdman"));
+}
+
+/// The directory archives are downloaded to before being extracted. Partial downloads
+/// are kept here so they can be resumed.
+pub fn get_downloads_dir() -> Result<PathBuf, GdmanError> {
+    return Ok(get_home_dir()?.join("downloads"));
 }
 
+/// The directory the godot link is created in, which needs to be on the PATH.
+/// This is the link-dir setting if set, otherwise the user's bin directory.
+pub fn get_link_dir() -> Result<PathBuf, GdmanError> {
+    if let Some(dir) = config::resolve::<PathBuf>(ConfigKey::LinkDir, None, None)? {
+        return Ok(dir);
+    }
+    return get_default_link_dir();
+}
+
+/// On unix, the link goes in $XDG_BIN_HOME, which defaults to ~/.local/bin
+#[cfg(unix)]
+fn get_default_link_dir() -> Result<PathBuf, GdmanError> {
+    if let Some(dir) = get_dir_from_env("XDG_BIN_HOME") {
+        return Ok(dir);
+    }
+    return match env::var_os("HOME") {
+        None => Err(GdmanError::Other(
+            "Cant determine link directory, HOME is not set".to_owned(),
+        )),
+        Some(home) => Ok(PathBuf::from(home).join(".local/bin")),
+    };
+}
+
+/// On Windows there's no conventional bin directory, so the link goes in the gdman home directory
+#[cfg(windows)]
+fn get_default_link_dir() -> Result<PathBuf, GdmanError> {
+    return Ok(get_home_dir()?.join("bin"));
+}
+
+fn get_dir_from_env(var: &str) -> Option<PathBuf> {
+    return env::var_os(var)
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from);
+}
+
+/// Before the home directory was configurable, versions were installed
+/// and linked in the directory containing the gdman executable
+fn get_legacy_home_dir() -> Result<PathBuf, GdmanError> {
+    return match env::current_exe() {
+        Ok(exe) => Ok(exe.parent().unwrap_or(Path::new("")).to_owned()),
+        Err(e) => Err(GdmanError::io(
+            "Cant determine the gdman executable path",
+            e,
+        )),
+    };
+}
+
+/// Moves any versions installed in the legacy location (alongside the gdman executable)
+/// into the versions directory, and recreates the godot link for the active version.
+/// The installer put the legacy location on the PATH, so unless the link directory has
+/// been set, it's kept as the link directory so the godot command carries on working.
+pub async fn migrate_legacy_install() -> Result<(), GdmanError> {
+    let legacy_dir = get_legacy_home_dir()?;
+    let legacy_versions_dir = legacy_dir.join("versions");
+
+    if !legacy_versions_dir.is_dir() {
+        return Ok(());
+    }
+
+    let versions_dir = get_versions_dir()?;
+
+    if is_same_dir(&legacy_versions_dir, &versions_dir) {
+        return Ok(());
+    }
+
+    let _lock = lock::lock().await?;
+
+    log::info!(
+        "Migrating versions of Godot from {} to {}",
+        legacy_versions_dir.display(),
+        versions_dir.display()
+    );
+
+    let default_link_dir = get_default_link_dir()?;
+    let keep_legacy_link_dir = config::resolve::<PathBuf>(ConfigKey::LinkDir, None, None)?
+        .is_none()
+        && !is_same_dir(&legacy_dir, &default_link_dir);
+    if keep_legacy_link_dir {
+        config::set(ConfigKey::LinkDir, Some(&legacy_dir.to_string_lossy()))?;
+        log::warn!(
+            "Keeping the godot link in {}, since the gdman installer adds it to your PATH. \
+            To move it to {} instead, add that to your PATH and run gdman config set link-dir {}",
+            legacy_dir.display(),
+            default_link_dir.display(),
+            default_link_dir.display()
+        );
+    }
+
+    // The legacy link will be broken once the versions are moved,
+    // so we need to find out which version is active beforehand
+    let legacy_link_path = legacy_dir.join(get_link_name());
+    let active_version_name = match fs::symlink_metadata(&legacy_link_path) {
+        Err(_) => None,
+        Ok(_) => get_link_target(&legacy_link_path)
+            .and_then(|target| get_version_dir_from_exe_path(&target))
+            .ok()
+            .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned())),
+    };
+
+    let entries = match fs::read_dir(&legacy_versions_dir) {
+        Err(e) => return Err(GdmanError::io("Error reading versions directory", e)),
+        Ok(entries) => entries,
+    };
+
+    for entry in entries.flatten() {
+        let target = versions_dir.join(entry.file_name());
+        if target.exists() {
+            log::warn!(
+                "{} already exists, leaving {} where it is",
+                target.display(),
+                entry.path().display()
+            );
+            continue;
+        }
+        log::trace!("Moving {} to {}", entry.path().display(), target.display());
+        if let Err(e) = fs::rename(entry.path(), &target) {
+            return Err(GdmanError::io(
+                format!(
+                    "Failed to move {} to {}, you may need to move it manually",
+                    entry.path().display(),
+                    target.display()
+                ),
+                e,
+            ));
+        }
+    }
+
+    if let Err(e) = fs::remove_dir(&legacy_versions_dir) {
+        log::warn!(
+            "Failed to remove legacy versions directory {}\n{e}",
+            legacy_versions_dir.display()
+        );
+    }
+
+    if let Some(version_name) = active_version_name {
+        remove_link(&legacy_link_path)?;
+        // The link directory setting was only just saved, so it wouldn't be picked up yet
+        match keep_legacy_link_dir {
+            true => link_godot_version(&legacy_link_path, &version_name)?,
+            false => set_active_godot_version(&version_name)?,
+        }
+    }
+
+    return Ok(());
+}
+
+pub fn is_same_dir(a: &Path, b: &Path) -> bool {
+    return match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    };
+}
+
+/// Downloads and extracts the version into a staging directory, then once it's been validated,
+/// moves it into the versions directory. This means a failed or interrupted install never leaves
+/// a half-populated version directory behind.
 pub async fn download_godot_version(
     version_name: &str,
+    release: &gh::Release,
     client: &reqwest::Client,
-    url: &str,
-) -> Result<PathBuf, String> {
-    log::info!("Getting {url}");
+    expected_sha512: &Option<String>,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf, GdmanError> {
+    return install_staged_version(version_name, async |dir_path, entry_prefix| {
+        download_and_extract(
+            release,
+            client,
+            dir_path,
+            entry_prefix,
+            expected_sha512,
+            progress,
+        )
+        .await
+    })
+    .await;
+}
+
+/// Extracts the archive on disk (e.g. a custom build) as the version, via a staging directory
+/// as with download_godot_version. The archive is left in place, since it isn't gdman's to delete.
+pub async fn install_archive(
+    archive_path: &Path,
+    version_name: &str,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf, GdmanError> {
+    log::info!("Installing {version_name} from {}", archive_path.display());
+
+    return install_staged_version(version_name, async |dir_path, entry_prefix| {
+        let file = match File::open(archive_path).await {
+            Err(e) => {
+                return Err(GdmanError::io(
+                    format!("Failed to open archive {}", archive_path.display()),
+                    e,
+                ))
+            }
+            Ok(f) => f,
+        };
+        unzip_file(file, dir_path, entry_prefix, progress).await
+    })
+    .await;
+}
+
+/// Names the version installed from an archive after the archive's file name, unless a name is
+/// specified. Either way the name needs to follow Godot's naming convention
+/// (e.g. Godot_v4.3-stable_linux.x86_64), as that's how gdman tells the installed versions apart.
+pub fn get_archive_version_name(
+    archive_name: &str,
+    name: &Option<String>,
+) -> Result<String, GdmanError> {
+    let version_name = match name {
+        Some(n) => n.clone(),
+        None => archive_name.trim_end_matches(".zip").to_owned(),
+    };
 
-    let version_zip_name = [version_name, "zip"].join(".");
-    let versions_dir_path = get_versions_dir()?;
+    if let Err(e) = parse_version_name(&version_name) {
+        log::trace!("{}", e.report());
+        return Err(GdmanError::InvalidInput(match name {
+            Some(_) => format!(
+                "The name {version_name} doesn't follow Godot's naming convention (e.g. Godot_v4.3-stable_linux.x86_64). \
+                To give a build a name of its own, extract it and use gdman link-custom instead"
+            ),
+            None => format!(
+                "{version_name} doesn't follow Godot's naming convention (e.g. Godot_v4.3-stable_linux.x86_64), so a name needs specifying for it"
+            ),
+        }));
+    }
+
+    return Ok(version_name);
+}
+
+/// Gets the name of the file at the URL, being the last segment of its path
+pub fn get_url_file_name(url: &str) -> Result<String, GdmanError> {
+    let parsed = match Url::parse(url) {
+        Err(e) => return Err(GdmanError::InvalidInput(format!("Invalid URL {url}\n{e}"))),
+        Ok(u) => u,
+    };
+    return match parsed.path_segments().and_then(|mut s| s.next_back()) {
+        Some(name) if !name.is_empty() => Ok(name.to_owned()),
+        _ => Err(GdmanError::InvalidInput(format!(
+            "{url} isn't the URL of a file"
+        ))),
+    };
+}
+
+/// Runs the extraction into a new staging directory, then moves the extracted version into the
+/// versions directory, returning its path. The extraction is given the directory to extract into,
+/// and the prefix to remove from the archive's entries, reducing nesting where the archive
+/// contains a folder matching the version name.
+async fn install_staged_version(
+    version_name: &str,
+    extract: impl AsyncFnOnce(&Path, &str) -> Result<(), GdmanError>,
+) -> Result<PathBuf, GdmanError> {
+    let version_dir_path = get_versions_dir()?.join(version_name);
 
-    let mut version_dir_path = versions_dir_path.clone();
-    version_dir_path.push(&version_name);
+    let staging_root = get_staging_dir()?;
+    remove_stale_staging_dirs(&staging_root);
 
-    let mut version_zip_path = version_dir_path.clone();
-    version_zip_path.push(&version_zip_name);
+    // The version is extracted into a directory with the same name as the
+    // final version directory, since that's what get_godot_exe_path expects
+    let staging_dir = StagingDir::create(&staging_root, version_name)?;
+    let staged_version_dir_path = staging_dir.path.join(version_name);
+
+    let entry_prefix = [version_name, "/"].join("");
+
+    let result = match extract(&staged_version_dir_path, &entry_prefix).await {
+        Err(e) => Err(e),
+        Ok(_) => move_staged_version(&staged_version_dir_path, &version_dir_path),
+    };
 
-    if version_zip_path.is_file() {
+    staging_dir.remove();
+
+    result?;
+
+    return Ok(version_dir_path);
+}
+
+/// Extracts export templates into a new staging directory, then moves them into the templates
+/// directory, so that templates which failed to extract aren't later taken to be installed.
+/// The staging directory is alongside the installed templates, so moving them is just a rename.
+async fn install_staged_templates(
+    templates_dir_path: &Path,
+    extract: impl AsyncFnOnce(&Path) -> Result<(), GdmanError>,
+) -> Result<(), GdmanError> {
+    let (templates_root, templates_name) =
+        match (templates_dir_path.parent(), templates_dir_path.file_name()) {
+            (Some(root), Some(name)) => (root, name.to_string_lossy()),
+            _ => {
+                return Err(GdmanError::Other(format!(
+                    "Invalid templates directory {}",
+                    templates_dir_path.display()
+                )))
+            }
+        };
+
+    let staging_root = templates_root.join(STAGING_DIR_NAME);
+    remove_stale_staging_dirs(&staging_root);
+
+    let staging_dir = StagingDir::create(&staging_root, &templates_name)?;
+    let staged_templates_dir_path = staging_dir.path.join(templates_name.as_ref());
+
+    let result = match extract(&staged_templates_dir_path).await {
+        Err(e) => Err(e),
+        Ok(_) => {
+            log::trace!(
+                "Moving {} to {}",
+                staged_templates_dir_path.display(),
+                templates_dir_path.display()
+            );
+            match fs::rename(&staged_templates_dir_path, templates_dir_path) {
+                Err(e) => Err(GdmanError::io(
+                    format!(
+                        "Failed to move {} to {}",
+                        staged_templates_dir_path.display(),
+                        templates_dir_path.display()
+                    ),
+                    e,
+                )),
+                Ok(_) => Ok(()),
+            }
+        }
+    };
+
+    staging_dir.remove();
+
+    return result;
+}
+
+pub fn get_staging_dir() -> Result<PathBuf, GdmanError> {
+    return Ok(get_versions_dir()?.join(STAGING_DIR_NAME));
+}
+
+/// A uniquely-named directory within a staging directory to extract into, with a lock file
+/// alongside it that's held for as long as the directory is in use. The lock file is created
+/// before the directory and deleted after it, so a directory whose lock file is missing or
+/// isn't locked was left behind by an install that was interrupted.
+struct StagingDir {
+    path: PathBuf,
+    lock_path: PathBuf,
+    _lock: fs::File,
+}
+
+impl StagingDir {
+    fn create(staging_root: &Path, name: &str) -> Result<StagingDir, GdmanError> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let suffix = format!("{:x}{:x}", std::process::id(), nanos);
+        let path = staging_root.join(format!("{name}-{suffix}"));
+        let lock_path = get_staging_lock_path(&path);
+
+        if let Err(e) = fs::create_dir_all(staging_root) {
+            return Err(GdmanError::io(
+                format!(
+                    "Failed to create staging directory {}",
+                    staging_root.display()
+                ),
+                e,
+            ));
+        }
+
+        let lock = match fs::File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Err(e) => {
+                return Err(GdmanError::io(
+                    format!("Failed to create lock file {}", lock_path.display()),
+                    e,
+                ))
+            }
+            Ok(f) => f,
+        };
+        if let Err(e) = lock.try_lock() {
+            return Err(GdmanError::io(
+                format!("Failed to lock {}", lock_path.display()),
+                e.into(),
+            ));
+        }
+
+        log::trace!("Creating staging directory {}", path.display());
+        if let Err(e) = fs::create_dir_all(&path) {
+            return Err(GdmanError::io(
+                format!("Failed to create staging directory {}", path.display()),
+                e,
+            ));
+        }
+
+        return Ok(StagingDir {
+            path,
+            lock_path,
+            _lock: lock,
+        });
+    }
+
+    /// Deletes the directory, then its lock file
+    fn remove(self) {
+        log::trace!("Deleting staging directory {}", self.path.display());
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            log::warn!(
+                "Failed to delete staging directory {}\n{e}",
+                self.path.display()
+            );
+            return;
+        }
+        if let Err(e) = fs::remove_file(&self.lock_path) {
+            log::warn!(
+                "Failed to delete lock file {}\n{e}",
+                self.lock_path.display()
+            );
+        }
+    }
+}
+
+/// Gets the path of the lock file held while the staging directory is in use
+pub fn get_staging_lock_path(staging_dir_path: &Path) -> PathBuf {
+    let mut lock_path = staging_dir_path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    return PathBuf::from(lock_path);
+}
+
+/// Checks the staged version contains a Godot executable, then moves it into place
+fn move_staged_version(
+    staged_dir_path: &PathBuf,
+    version_dir_path: &Path,
+) -> Result<(), GdmanError> {
+    let exe_path = match get_godot_exe_path(staged_dir_path) {
+        Err(_) => {
+            return Err(GdmanError::InvalidArchive(
+                "The archive doesn't appear to contain Godot".to_owned(),
+            ))
+        }
+        Ok(p) => p,
+    };
+
+    ensure_executable(&exe_path)?;
+
+    // Anything already here isn't a complete installation, or we wouldn't be installing it
+    if version_dir_path.exists() {
         log::trace!(
-            "Removing old zip file from previous installation attempt, {}",
-            version_zip_path.display()
+            "Removing incomplete installation {}",
+            version_dir_path.display()
         );
-        fs::remove_file(&version_zip_path).or(Err(format!(
-            "Error removing old zip file from previous installation attempt\n{}",
-            version_zip_path.display()
-        )))?;
+        if let Err(e) = fs::remove_dir_all(version_dir_path) {
+            return Err(GdmanError::io(
+                format!(
+                    "Failed to remove incomplete installation {}",
+                    version_dir_path.display()
+                ),
+                e,
+            ));
+        }
     }
 
-    let url = Url::parse(url).or(Err("Invalid URL"))?;
+    log::trace!(
+        "Moving {} to {}",
+        staged_dir_path.display(),
+        version_dir_path.display()
+    );
+    if let Err(e) = fs::rename(staged_dir_path, version_dir_path) {
+        return Err(GdmanError::io(
+            format!(
+                "Failed to move {} to {}",
+                staged_dir_path.display(),
+                version_dir_path.display()
+            ),
+            e,
+        ));
+    }
 
-    let file = download_file(client, url, &version_zip_path).await?;
+    return Ok(());
+}
 
-    unzip_file(file, &version_dir_path).await?;
+/// Deletes the directories left in the staging directory by installs that were interrupted,
+/// leaving those still being extracted into by other installs
+fn remove_stale_staging_dirs(staging_dir: &Path) {
+    let entries = match fs::read_dir(staging_dir) {
+        Err(_) => return,
+        Ok(e) => e,
+    };
+    for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+        let path = entry.path();
+        let lock_path = get_staging_lock_path(&path);
+
+        // Without a lock file the directory is stale, since it's created before the directory
+        let lock = match fs::File::options().read(true).write(true).open(&lock_path) {
+            Err(_) => None,
+            Ok(file) => match file.try_lock() {
+                Err(_) => {
+                    log::trace!("Staging directory {} is in use", path.display());
+                    continue;
+                }
+                Ok(()) => Some(file),
+            },
+        };
 
-    log::trace!("Deleting zip file {}", &version_zip_path.display());
-    if let Err(e) = remove_file(&version_zip_path) {
-        return Err(e.to_string());
+        log::trace!("Deleting stale staging directory {}", path.display());
+        if let Err(e) = fs::remove_dir_all(&path) {
+            log::warn!(
+                "Failed to delete stale staging directory {}\n{e}",
+                path.display()
+            );
+            continue;
+        }
+        if lock.is_some() {
+            if let Err(e) = fs::remove_file(&lock_path) {
+                log::warn!("Failed to delete lock file {}\n{e}", lock_path.display());
+            }
+        }
     }
+}
 
-    return Ok(version_dir_path);
+/// Downloads the release's archive asset (being the first asset of the release) into the output
+/// directory, then verifies and extracts it as described by extract_archive. Each of the mirrors
+/// is tried in turn, falling back to the release source if the archive can't be downloaded,
+/// verified or extracted from any of them.
+async fn download_and_extract(
+    release: &gh::Release,
+    client: &reqwest::Client,
+    out_dir_path: &Path,
+    entry_prefix: &str,
+    expected_sha512: &Option<String>,
+    progress: &dyn ProgressReporter,
+) -> Result<(), GdmanError> {
+    let asset = match release.assets.first() {
+        None => {
+            return Err(GdmanError::Other(format!(
+                "Release {} has no assets",
+                release.tag_name
+            )))
+        }
+        Some(a) => a,
+    };
+    let archive_path = get_downloads_dir()?.join(&asset.name);
+
+    for mirror in mirrors::from_config()? {
+        let url = mirror.get_asset_url(&release.tag_name, &asset.name);
+        log::info!("Getting {url}");
+
+        let result = match download_file(client, &url, &archive_path, progress).await {
+            Err(e) => Err(e),
+            Ok(_) => {
+                extract_archive(
+                    &archive_path,
+                    out_dir_path,
+                    entry_prefix,
+                    expected_sha512,
+                    progress,
+                )
+                .await
+            }
+        };
+
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                log::warn!(
+                    "Failed to download {} from {mirror}, trying the next mirror\n{}",
+                    asset.name,
+                    e.report()
+                );
+                // Anything left from a failed extraction would be mixed up with the next attempt
+                if out_dir_path.exists() {
+                    if let Err(e) = fs::remove_dir_all(out_dir_path) {
+                        return Err(GdmanError::io(
+                            format!("Failed to delete {}", out_dir_path.display()),
+                            e,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    log::info!("Getting {}", asset.browser_download_url);
+
+    release_source::from_config()?
+        .fetch_asset(asset, &archive_path, client, progress)
+        .await?;
+
+    return extract_archive(
+        &archive_path,
+        out_dir_path,
+        entry_prefix,
+        expected_sha512,
+        progress,
+    )
+    .await;
+}
+
+/// Verifies the downloaded archive against the expected checksum (if specified), then extracts
+/// it into the output directory and deletes it. Entries within the archive have the specified
+/// prefix removed from their paths. If the checksum doesn't match, the archive is deleted.
+async fn extract_archive(
+    archive_path: &Path,
+    out_dir_path: &Path,
+    entry_prefix: &str,
+    expected_sha512: &Option<String>,
+    progress: &dyn ProgressReporter,
+) -> Result<(), GdmanError> {
+    let archive_name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let (file, sha512) = open_and_hash_file(archive_path).await?;
+
+    if let Some(expected_sha512) = expected_sha512 {
+        if sha512 != *expected_sha512 {
+            drop(file);
+            log::trace!(
+                "Checksum mismatch, deleting archive {}",
+                archive_path.display()
+            );
+            if let Err(e) = fs::remove_file(archive_path) {
+                log::warn!("Failed to delete archive {}\n{e}", archive_path.display());
+            }
+            return Err(GdmanError::ChecksumMismatch {
+                name: archive_name,
+                expected: expected_sha512.clone(),
+                actual: sha512,
+            });
+        }
+        log::info!("Verified checksum of {archive_name}");
+    }
+
+    unzip_file(file, out_dir_path, entry_prefix, progress).await?;
+
+    log::trace!("Deleting archive {}", &archive_path.display());
+    if let Err(e) = remove_file(archive_path) {
+        return Err(GdmanError::io(
+            format!("Failed to delete archive {}", archive_path.display()),
+            e,
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Finds the latest release satisfying the specified version (or the latest release on
+/// the channel, if no version is specified) with an asset for the specified platform,
+/// architecture and flavour. The asset is the first of the release's assets.
+pub async fn resolve_release(
+    version: &Option<MaybeVersionOrVersionReq>,
+    platform: &Platform,
+    architecture: &Architecture,
+    flavour: &Flavour,
+    channel: &Channel,
+    client: &reqwest::Client,
+) -> Result<gh::Release, GdmanError> {
+    let (_, version_like, version_exact) = flatten_version(version);
+
+    return gd::find_release_with_asset(
+        &version_exact,
+        &version_like,
+        platform,
+        architecture,
+        flavour,
+        channel,
+        client,
+    )
+    .await;
+}
+
+/// Downloads and extracts the release asset (being the first asset of the release,
+/// as returned by godot_repo::find_release_with_asset), returning the path to the
+/// installed version directory. The version is not set active.
+pub async fn install_release(
+    release: &gh::Release,
+    client: &reqwest::Client,
+    skip_verify: bool,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf, GdmanError> {
+    let asset = match release.assets.first() {
+        None => {
+            return Err(GdmanError::Other(format!(
+                "Release {} has no assets",
+                release.tag_name
+            )))
+        }
+        Some(a) => a,
+    };
+    let version_name = asset.name.trim_end_matches(".zip");
+
+    if releases_cache::is_offline() {
+        return Err(GdmanError::Offline(format!(
+            "{version_name} is not installed, and can't be downloaded while offline"
+        )));
+    }
+
+    let expected_sha512 = get_expected_checksum(release, &asset.name, client, skip_verify).await?;
+
+    return download_godot_version(version_name, release, client, &expected_sha512, progress).await;
+}
+
+/// Downloads and extracts the export templates (being the first asset of the release,
+/// as returned by godot_repo::find_release_with_templates) into the directory Godot expects.
+pub async fn install_templates(
+    release: &gh::Release,
+    flavour: &Flavour,
+    client: &reqwest::Client,
+    skip_verify: bool,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf, GdmanError> {
+    let asset = match release.assets.first() {
+        None => {
+            return Err(GdmanError::Other(format!(
+                "Release {} has no assets",
+                release.tag_name
+            )))
+        }
+        Some(a) => a,
+    };
+
+    let version = parse_semver_version(&release.tag_name, &Some(vec!["stable".to_owned()]))?;
+    let templates_dir_path =
+        get_templates_dir(&version)?.join(get_templates_dir_name(&release.tag_name, flavour));
+
+    if templates_dir_path.is_dir() {
+        log::info!(
+            "Export templates for {} already installed",
+            release.tag_name
+        );
+        return Ok(templates_dir_path);
+    }
+
+    if releases_cache::is_offline() {
+        return Err(GdmanError::Offline(format!(
+            "Export templates for {} are not installed, and can't be downloaded while offline",
+            release.tag_name
+        )));
+    }
+
+    let expected_sha512 = get_expected_checksum(release, &asset.name, client, skip_verify).await?;
+
+    // The templates are all contained within a templates folder inside the archive
+    install_staged_templates(&templates_dir_path, async |out_dir_path| {
+        download_and_extract(
+            release,
+            client,
+            out_dir_path,
+            "templates/",
+            &expected_sha512,
+            progress,
+        )
+        .await
+    })
+    .await?;
+
+    log::info!(
+        "Installed export templates to {}",
+        templates_dir_path.display()
+    );
+
+    return Ok(templates_dir_path);
+}
+
+/// Installs the export templates matching the specified version, unless they're already installed.
+pub async fn install_templates_if_missing(
+    name_parts: &GodotVersionNameParts,
+    client: &reqwest::Client,
+    skip_verify: bool,
+    progress: &dyn ProgressReporter,
+) -> Result<PathBuf, GdmanError> {
+    let templates_dir_path = get_templates_dir(&name_parts.version)?.join(get_templates_dir_name(
+        &name_parts.version_string,
+        &name_parts.flavour,
+    ));
+
+    if templates_dir_path.is_dir() {
+        log::info!(
+            "Export templates for {} already installed",
+            name_parts.version_name
+        );
+        return Ok(templates_dir_path);
+    }
+
+    let release = gd::find_release_with_templates(
+        &Some(name_parts.version.clone()),
+        &None,
+        &name_parts.flavour,
+        &Channel::Stable,
+        client,
+    )
+    .await?;
+
+    return install_templates(&release, &name_parts.flavour, client, skip_verify, progress).await;
+}
+
+async fn get_expected_checksum(
+    release: &gh::Release,
+    asset_name: &str,
+    client: &reqwest::Client,
+    skip_verify: bool,
+) -> Result<Option<String>, GdmanError> {
+    return match skip_verify {
+        true => {
+            log::warn!("Skipping checksum verification of {asset_name}");
+            Ok(None)
+        }
+        false => Ok(Some(
+            gd::get_asset_checksum(release, asset_name, client).await?,
+        )),
+    };
+}
+
+pub fn get_installed_templates() -> Result<Vec<TemplatesInfo>, GdmanError> {
+    log::trace!("Checking installed export templates");
+    let mut templates: Vec<TemplatesInfo> = Vec::new();
+
+    for dir_name in [GODOT_3_TEMPLATES_DIR_NAME, GODOT_4_TEMPLATES_DIR_NAME] {
+        let templates_dir = get_godot_data_dir()?.join(dir_name);
+        if !templates_dir.is_dir() {
+            continue;
+        }
+
+        log::trace!("Reading templates directory {}", templates_dir.display());
+
+        let entries = match fs::read_dir(&templates_dir) {
+            Err(e) => return Err(GdmanError::io("Error reading templates directory", e)),
+            Ok(entries) => entries,
+        };
+
+        for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match parse_templates_dir_name(&name) {
+                None => log::trace!("Skipping unrecognized templates directory {name}"),
+                Some((version, flavour)) => templates.push(TemplatesInfo {
+                    path: entry.path(),
+                    name,
+                    version,
+                    flavour,
+                }),
+            }
+        }
+    }
+
+    return Ok(templates);
+}
+
+pub fn uninstall_templates(templates: &TemplatesInfo) -> Result<(), GdmanError> {
+    log::trace!("Uninstalling export templates {}", templates.name);
+    if let Err(e) = fs::remove_dir_all(&templates.path) {
+        return Err(GdmanError::io(
+            format!("Error uninstalling export templates {}", templates.name),
+            e,
+        ));
+    }
+    Ok(())
+}
+
+/// Uninstalls the oldest versions so that no more than the specified number of versions
+/// of each flavour, architecture and platform are installed. The currently-active version
+/// is always kept.
+pub fn uninstall_old_versions(keep: usize) -> Result<(), GdmanError> {
+    let current = get_current_version().ok().filter(|c| !c.custom);
+    let current_name = current.as_ref().map(|c| c.name_parts.version_name.clone());
+
+    // Custom builds are only ever uninstalled explicitly
+    let mut versions: Vec<GodotVersionInfo> = get_installed_versions()?
+        .into_iter()
+        .filter(|v| !v.custom)
+        .collect();
+    versions.sort_by(|a, b| compare_versions(&b.name_parts.version, &a.name_parts.version));
+
+    // Versions are counted separately for each flavour, architecture and platform, so that
+    // e.g. installing a mono version doesn't uninstall the standard ones
+    let is_same_group = |a: &GodotVersionNameParts, b: &GodotVersionNameParts| {
+        a.flavour == b.flavour && a.architecture == b.architecture && a.platform == b.platform
+    };
+    let mut kept: Vec<(GodotVersionNameParts, usize)> =
+        current.into_iter().map(|c| (c.name_parts, 1)).collect();
+
+    for version in versions {
+        if Some(&version.name_parts.version_name) == current_name.as_ref() {
+            continue;
+        }
+        let group = match kept
+            .iter()
+            .position(|(name_parts, _)| is_same_group(name_parts, &version.name_parts))
+        {
+            Some(i) => i,
+            None => {
+                kept.push((version.name_parts.clone(), 0));
+                kept.len() - 1
+            }
+        };
+        if kept[group].1 < keep {
+            kept[group].1 += 1;
+            continue;
+        }
+        uninstall_version(&version)?;
+        log::info!(
+            "Uninstalled old version {}",
+            version.name_parts.version_name
+        );
+    }
+
+    return Ok(());
 }
 
-pub fn uninstall_version(version: &GodotVersionInfo) -> Result<(), String> {
+/// Uninstalls the version, or for a custom build, forgets about it without deleting anything
+pub fn uninstall_version(version: &GodotVersionInfo) -> Result<(), GdmanError> {
+    if version.custom {
+        log::trace!("Unlinking custom build {}", version.name_parts.version_name);
+        custom::unlink_custom_version(&version.name_parts.version_name)?;
+        log::info!(
+            "Left {} in place, since gdman didn't install it",
+            version.path.display()
+        );
+        return Ok(());
+    }
+
     log::trace!("Uninstalling version {}", version.name_parts.version);
-    if let Err(err) = fs::remove_dir_all(version.path.parent().unwrap()) {
-        return Err(format!(
-            "Error uninstalling version {}\n{}",
-            version.name_parts.version_name, err
+    if let Err(e) = fs::remove_dir_all(&version.path) {
+        return Err(GdmanError::io(
+            format!(
+                "Error uninstalling version {}",
+                version.name_parts.version_name
+            ),
+            e,
         ));
     }
     Ok(())
@@ -158,10 +1107,14 @@ pub fn activate_by_parts_if_installed(
     platform: &Platform,
     architecture: &Architecture,
     flavour: &Flavour,
-) -> Result<bool, String> {
+) -> Result<bool, GdmanError> {
     if let Some(v) = version {
         let installed_versions = get_installed_versions()?;
         for installed_version in installed_versions {
+            // Custom builds are only activated by name
+            if installed_version.custom {
+                continue;
+            }
             if &installed_version.name_parts.architecture == architecture
                 && &installed_version.name_parts.flavour == flavour
                 && &installed_version.name_parts.platform == platform
@@ -179,144 +1132,312 @@ pub fn activate_by_parts_if_installed(
     return Ok(false);
 }
 
-pub fn activate_by_name_if_installed(name: &str) -> Result<bool, String> {
+pub fn activate_by_name_if_installed(name: &str) -> Result<bool, GdmanError> {
     if already_installed(name) {
         log::trace!("Version {name} already installed, setting active");
-        match set_active_godot_version(name) {
-            Err(e) => {
-                return Err(format!(
-                    "Error when trying to activate currently installed version {name}\n{e}"
-                ))
-            }
-            Ok(_) => return Ok(true),
-        }
+        set_active_godot_version(name)?;
+        return Ok(true);
     } else {
         log::trace!("Version {name} not yet installed");
     }
     return Ok(false);
 }
 
-pub fn get_installed_versions() -> Result<Vec<GodotVersionInfo>, String> {
+/// Finds the latest installed version which satisfies the specified version
+/// and matches the specified platform, architecture and flavour.
+pub fn find_installed_version(
+    version: &MaybeVersionOrVersionReq,
+    platform: &Platform,
+    architecture: &Architecture,
+    flavour: &Flavour,
+) -> Result<Option<GodotVersionInfo>, GdmanError> {
+    return Ok(get_installed_versions()?
+        .into_iter()
+        .filter(|v| {
+            !v.custom
+                && &v.name_parts.platform == platform
+                && &v.name_parts.architecture == architecture
+                && &v.name_parts.flavour == flavour
+                && match &version.version_exact {
+                    Some(exact) => &v.name_parts.version == exact,
+                    None => version.version_like.matches(&v.name_parts.version),
+                }
+        })
+        .max_by(|a, b| compare_versions(&a.name_parts.version, &b.name_parts.version)));
+}
+
+/// Gets the versions of Godot installed in the versions directory, followed by the custom builds,
+/// warning about anything in the versions directory that isn't recognized as a version of Godot
+pub fn get_installed_versions() -> Result<Vec<GodotVersionInfo>, GdmanError> {
+    let contents = read_versions_dir()?;
+
+    // This gets called several times by some commands, but the warnings only need showing once
+    WARN_UNRECOGNIZED.call_once(|| {
+        for path in contents.unrecognized {
+            log::warn!(
+                "Ignoring {}, it's not a recognized version of Godot",
+                path.display()
+            );
+        }
+    });
+
+    let mut versions = contents.versions;
+    versions.extend(
+        custom::get_custom_versions()?
+            .iter()
+            .map(|c| c.to_version_info()),
+    );
+
+    return Ok(versions);
+}
+
+/// Reads the versions directory, separating the versions of Godot from the directories
+/// which aren't recognized as one, e.g. custom builds or directories created by the user.
+pub fn read_versions_dir() -> Result<VersionsDirContents, GdmanError> {
     log::trace!("Checking installed versions");
     let versions_dir = get_versions_dir()?;
     log::trace!("Reading versions directory {}", versions_dir.display());
 
     let entries = match fs::read_dir(&versions_dir) {
-        Err(e) => return Err(format!("Error reading versions directory\n{e}")),
+        Err(e) => return Err(GdmanError::io("Error reading versions directory", e)),
         Ok(entries) => entries,
     };
 
-    return Ok(entries
-        .filter(|e| {
-            e.as_ref()
-                .unwrap()
-                .file_name()
-                .to_string_lossy()
-                .starts_with("Godot_")
-        })
-        .filter_map(|entry| {
-            entry.ok().and_then(|e| {
-                Some(GodotVersionInfo {
-                    name_parts: parse_version_name(&e.file_name().to_str().unwrap()).unwrap(),
-                    path: e.path(),
-                })
-            })
-        })
-        .collect::<Vec<GodotVersionInfo>>());
+    let mut contents = VersionsDirContents {
+        versions: Vec::new(),
+        unrecognized: Vec::new(),
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        // Hidden directories (e.g. staging) and files (e.g. archives left by
+        // older versions of gdman) can't be versions of Godot
+        if name.starts_with('.') || !path.is_dir() {
+            log::trace!("Skipping {}", path.display());
+            continue;
+        }
+
+        match parse_installed_version_name(&name) {
+            Err(e) => {
+                log::trace!("{}", e.report());
+                contents.unrecognized.push(path);
+            }
+            Ok(name_parts) => contents.versions.push(GodotVersionInfo {
+                path,
+                name_parts,
+                custom: false,
+            }),
+        }
+    }
+
+    return Ok(contents);
+}
+
+fn parse_installed_version_name(name: &str) -> Result<GodotVersionNameParts, GdmanError> {
+    return parse_version_name(name).map_err(|e| GdmanError::InvalidVersionName {
+        name: name.to_owned(),
+        reason: e.report(),
+    });
+}
+
+pub struct VersionsDirContents {
+    pub versions: Vec<GodotVersionInfo>,
+    /// Directories which aren't recognized as a version of Godot
+    pub unrecognized: Vec<PathBuf>,
 }
 
 #[derive(Clone)]
 pub struct GodotVersionInfo {
+    /// The version directory, or for a custom build, its executable
     pub path: PathBuf,
     pub name_parts: GodotVersionNameParts,
+    /// Whether this is a custom build registered via link-custom, rather than a version gdman installed
+    pub custom: bool,
+}
+
+#[derive(Clone)]
+pub struct TemplatesInfo {
+    pub path: PathBuf,
+    pub name: String,
+    pub version: semver::Version,
+    pub flavour: Flavour,
 }
 
-pub fn get_current_version() -> Result<GodotVersionInfo, String> {
+impl TemplatesInfo {
+    /// Checks if these templates are for the specified version of Godot
+    pub fn matches(&self, version: &GodotVersionInfo) -> bool {
+        return self.version == version.name_parts.version
+            && self.flavour == version.name_parts.flavour;
+    }
+}
+
+pub fn get_current_version() -> Result<GodotVersionInfo, GdmanError> {
     let path = get_godot_link_path()?;
 
     if path.exists() {
         log::trace!("Found godot link path {}", path.display());
     } else {
-        return Err(format!(
-            "Cant determine current version, godot link not found at {}",
-            path.display()
-        )
-        .to_owned());
+        return Err(GdmanError::NoActiveVersion(path));
     }
 
     let target = get_link_target(&path)?;
 
+    // Custom builds live outside the versions directory, so are recognized by their executable
+    if let Some(custom) = custom::find_custom_version_by_exe(&target)? {
+        log::trace!(
+            "Found active version of Godot to be custom build {}",
+            custom.name_parts.version_name
+        );
+        return Ok(custom.to_version_info());
+    }
+
     let version_dir = get_version_dir_from_exe_path(&target)?;
 
     log::trace!("Found version directory {}", version_dir.display());
 
-    let version_name = version_dir.file_name().unwrap();
+    let version_name = version_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let current_info = GodotVersionInfo {
+        name_parts: parse_installed_version_name(&version_name)?,
+        path: version_dir,
+        custom: false,
+    };
+
+    log::trace!(
+        "Found active version of Godot to be {}, (platform = {}, architecture = {}, flavour = {})",
+        current_info.name_parts.version.to_string(),
+        current_info.name_parts.platform,
+        current_info.name_parts.architecture,
+        current_info.name_parts.flavour
+    );
+
+    return Ok(current_info);
+}
+
+/// Replaces the current process with the Godot executable, forwarding the specified args.
+/// This only returns if Godot could not be launched.
+#[cfg(unix)]
+pub fn exec_godot(exe_path: &Path, args: &[String]) -> Result<(), GdmanError> {
+    use std::os::unix::process::CommandExt;
+
+    log::trace!("Executing {} {}", exe_path.display(), args.join(" "));
+
+    let err = Command::new(exe_path).args(args).exec();
+
+    return Err(GdmanError::io(
+        format!("Failed to launch {}", exe_path.display()),
+        err,
+    ));
+}
+
+/// Launches the Godot executable, forwarding the specified args, then exits
+/// with the Godot exit code once it has finished.
+#[cfg(windows)]
+pub fn exec_godot(exe_path: &Path, args: &[String]) -> Result<(), GdmanError> {
+    log::trace!("Executing {} {}", exe_path.display(), args.join(" "));
+
+    let status = match Command::new(exe_path).args(args).status() {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to launch {}", exe_path.display()),
+                e,
+            ))
+        }
+        Ok(s) => s,
+    };
+
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Creates the directory the link goes in if needed, warning if it's not on the PATH
+fn create_link_dir(link_path: &Path) -> Result<(), GdmanError> {
+    let link_dir = link_path.parent().unwrap_or(Path::new(""));
+
+    if let Err(e) = fs::create_dir_all(link_dir) {
+        return Err(GdmanError::io(
+            format!("Failed to create link directory {}", link_dir.display()),
+            e,
+        ));
+    }
 
-    let current_info = GodotVersionInfo {
-        path: target,
-        name_parts: gd::parse_version_name(version_name.to_str().unwrap())?,
-    };
+    let on_path = env::var_os("PATH")
+        .map(|p| env::split_paths(&p).any(|dir| is_same_dir(&dir, link_dir)))
+        .unwrap_or(false);
 
-    log::trace!(
-        "Found active version of Godot to be {}, (platform = {}, architecture = {}, flavour = {})",
-        current_info.name_parts.version.to_string(),
-        current_info.name_parts.platform,
-        current_info.name_parts.architecture,
-        current_info.name_parts.flavour
-    );
+    if !on_path {
+        log::warn!(
+            "{} is not on your PATH, add it to be able to use the godot command",
+            link_dir.display()
+        );
+    }
 
-    return Ok(current_info);
+    return Ok(());
 }
 
 #[cfg(windows)]
-fn create_link(link_path: &PathBuf, target_path: &PathBuf) -> Result<(), String> {
-    let sl = mslnk::ShellLink::new(target_path).or(Err("Failed to create ShellLink"))?;
-    sl.create_lnk(link_path)
-        .or(Err("Failed to create Godot shortcut"))?;
+fn create_link(link_path: &PathBuf, target_path: &PathBuf) -> Result<(), GdmanError> {
+    let sl = match mslnk::ShellLink::new(target_path) {
+        Err(e) => {
+            return Err(GdmanError::Other(format!(
+                "Failed to create ShellLink\n{e}"
+            )))
+        }
+        Ok(sl) => sl,
+    };
+    if let Err(e) = sl.create_lnk(link_path) {
+        return Err(GdmanError::Other(format!(
+            "Failed to create Godot shortcut\n{e}"
+        )));
+    }
 
     return Ok(());
 }
 
 #[cfg(unix)]
-fn create_link(link_path: &PathBuf, target_path: &PathBuf) -> Result<(), String> {
-    if let Err(err) = symlink::symlink_file(target_path, link_path) {
-        return Err(format!(
-            "Failed to create Godot symlink,\n{}",
-            err.to_string()
-        ));
+fn create_link(link_path: &PathBuf, target_path: &PathBuf) -> Result<(), GdmanError> {
+    if let Err(e) = symlink::symlink_file(target_path, link_path) {
+        return Err(GdmanError::io("Failed to create Godot symlink", e));
     }
     return Ok(());
 }
 
 #[cfg(windows)]
-fn remove_link(link_path: &PathBuf) -> Result<(), String> {
+pub fn remove_link(link_path: &PathBuf) -> Result<(), GdmanError> {
     if link_path.is_file() {
         log::trace!("Removing old godot link {}", link_path.display());
-        fs::remove_file(link_path).or(Err("Error deleting Windows Godot shortcut"))?;
+        if let Err(e) = fs::remove_file(link_path) {
+            return Err(GdmanError::io("Error deleting Windows Godot shortcut", e));
+        }
     }
     return Ok(());
 }
 
 #[cfg(unix)]
-fn remove_link(link_path: &PathBuf) -> Result<(), String> {
-    match fs::symlink_metadata(&link_path) {
-        Ok(_) => {
-            log::trace!("Removing old godot link {}", link_path.display());
-            if let Err(e) = fs::remove_file(&link_path) {
-                return Err(e.to_string().to_owned());
-            }
+pub fn remove_link(link_path: &PathBuf) -> Result<(), GdmanError> {
+    if fs::symlink_metadata(link_path).is_ok() {
+        log::trace!("Removing old godot link {}", link_path.display());
+        if let Err(e) = fs::remove_file(link_path) {
+            return Err(GdmanError::io(
+                format!("Error deleting godot link {}", link_path.display()),
+                e,
+            ));
         }
-        Err(_) => {}
     }
     return Ok(());
 }
 
 #[cfg(unix)]
-fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, String> {
+pub fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, GdmanError> {
     return match fs::read_link(link_path) {
-        Err(e) => Err(format!(
-            "Cant determine current version, godot link may be broken\n{e}"
-        )),
+        Err(e) => Err(GdmanError::BrokenLink {
+            path: link_path.clone(),
+            reason: e.to_string(),
+        }),
         Ok(target) => {
             log::trace!("Godot link points to {}", target.display());
             return Ok(target);
@@ -325,19 +1446,27 @@ fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, String> {
 }
 
 #[cfg(windows)]
-fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, String> {
-    let target = lnk::ShellLink::open(link_path).unwrap();
+pub fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, GdmanError> {
+    let broken = |reason: &str| GdmanError::BrokenLink {
+        path: link_path.clone(),
+        reason: reason.to_owned(),
+    };
+
+    let target = match lnk::ShellLink::open(link_path) {
+        Err(e) => return Err(broken(&format!("{e:?}"))),
+        Ok(t) => t,
+    };
     log::trace!("Found lnk data {:#?}", target);
 
-    let working_dir = target
-        .working_dir()
-        .as_ref()
-        .expect("Godot shortcut has no working directory");
+    let working_dir = match target.working_dir() {
+        None => return Err(broken("Godot shortcut has no working directory")),
+        Some(d) => d,
+    };
 
-    let relative_path = target
-        .relative_path()
-        .as_ref()
-        .expect("Godot shortcut has no relative path");
+    let relative_path = match target.relative_path() {
+        None => return Err(broken("Godot shortcut has no relative path")),
+        Some(p) => p,
+    };
 
     let target_path = PathBuf::from_iter([working_dir, relative_path]);
 
@@ -345,26 +1474,118 @@ fn get_link_target(link_path: &PathBuf) -> Result<PathBuf, String> {
 }
 
 #[cfg(any(windows, target_os = "linux"))]
-fn get_version_dir_from_exe_path(exe_path: &PathBuf) -> Result<PathBuf, String> {
-    return Ok(exe_path.parent().unwrap().to_path_buf());
+pub fn get_version_dir_from_exe_path(exe_path: &Path) -> Result<PathBuf, GdmanError> {
+    return match exe_path.parent() {
+        None => Err(GdmanError::Other(format!(
+            "{} is not within a version directory",
+            exe_path.display()
+        ))),
+        Some(dir) => Ok(dir.to_path_buf()),
+    };
 }
 
 #[cfg(target_os = "macos")]
-fn get_version_dir_from_exe_path(exe_path: &PathBuf) -> Result<PathBuf, String> {
+pub fn get_version_dir_from_exe_path(exe_path: &Path) -> Result<PathBuf, GdmanError> {
     // exe_path will be something like Godot_v1.2.3/Godot/Contents/MacOS/Godot
     // We need to backtrack 4 parents to get the version dir
-    return Ok(exe_path.ancestors().nth(4).unwrap().to_path_buf());
+    return match exe_path.ancestors().nth(4) {
+        None => Err(GdmanError::Other(format!(
+            "{} is not within a version directory",
+            exe_path.display()
+        ))),
+        Some(dir) => Ok(dir.to_path_buf()),
+    };
+}
+
+/// On linux, user data is stored under $XDG_DATA_HOME, which defaults to ~/.local/share
+#[cfg(target_os = "linux")]
+fn get_user_data_dir() -> Result<PathBuf, GdmanError> {
+    if let Some(dir) = get_dir_from_env("XDG_DATA_HOME") {
+        return Ok(dir);
+    }
+    return match env::var_os("HOME") {
+        None => Err(GdmanError::Other(
+            "Cant determine user data directory, HOME is not set".to_owned(),
+        )),
+        Some(home) => Ok(PathBuf::from(home).join(".local/share")),
+    };
+}
+
+/// On Windows, user data is stored under %APPDATA%
+#[cfg(windows)]
+fn get_user_data_dir() -> Result<PathBuf, GdmanError> {
+    return match env::var_os("APPDATA") {
+        None => Err(GdmanError::Other(
+            "Cant determine user data directory, APPDATA is not set".to_owned(),
+        )),
+        Some(app_data) => Ok(PathBuf::from(app_data)),
+    };
+}
+
+/// On MacOS, user data is stored under ~/Library/Application Support
+#[cfg(target_os = "macos")]
+fn get_user_data_dir() -> Result<PathBuf, GdmanError> {
+    return match env::var_os("HOME") {
+        None => Err(GdmanError::Other(
+            "Cant determine user data directory, HOME is not set".to_owned(),
+        )),
+        Some(home) => Ok(PathBuf::from(home).join("Library/Application Support")),
+    };
+}
+
+/// Godot stores user data (e.g. export templates) in a godot directory on linux, and Godot elsewhere
+fn get_godot_data_dir() -> Result<PathBuf, GdmanError> {
+    let dir_name = match env::consts::OS {
+        "linux" => "godot",
+        _ => "Godot",
+    };
+    return Ok(get_user_data_dir()?.join(dir_name));
+}
+
+/// Godot 3 expects export templates in a templates directory, whereas Godot 4 uses export_templates
+fn get_templates_dir(version: &semver::Version) -> Result<PathBuf, GdmanError> {
+    let dir_name = match version.major {
+        0..=3 => GODOT_3_TEMPLATES_DIR_NAME,
+        _ => GODOT_4_TEMPLATES_DIR_NAME,
+    };
+    return Ok(get_godot_data_dir()?.join(dir_name));
+}
+
+/// Godot expects the templates for each version to be in a directory
+/// named after the version and status, e.g. 4.2.1.stable or 4.2.1.stable.mono
+fn get_templates_dir_name(version_string: &str, flavour: &Flavour) -> String {
+    let mut name = version_string.replacen('-', ".", 1);
+    if *flavour == Flavour::Mono {
+        name.push_str(".mono");
+    }
+    return name;
+}
+
+fn parse_templates_dir_name(name: &str) -> Option<(semver::Version, Flavour)> {
+    let (name, flavour) = match name.strip_suffix(".mono") {
+        Some(n) => (n, Flavour::Mono),
+        None => (name, Flavour::Standard),
+    };
+    let (version, status) = name.rsplit_once('.')?;
+    let version = parse_semver_version(
+        &format!("{version}-{status}"),
+        &Some(vec!["stable".to_owned()]),
+    )
+    .ok()?;
+    return Some((version, flavour));
 }
 
-fn get_godot_link_path() -> Result<PathBuf, String> {
-    let link_name = match env::consts::OS {
+fn get_link_name() -> &'static str {
+    return match env::consts::OS {
         "windows" => "godot.lnk",
         _ => "godot",
     };
+}
 
-    let base_dir = get_base_dir()?;
+pub fn get_godot_link_path() -> Result<PathBuf, GdmanError> {
+    let link_dir = get_link_dir()?;
 
-    let link_path: PathBuf = base_dir.join(link_name);
+    let link_path: PathBuf = link_dir.join(get_link_name());
 
     return Ok(link_path);
 }
@@ -372,7 +1593,7 @@ fn get_godot_link_path() -> Result<PathBuf, String> {
 /// On linux, the Godot executable is expected to be the only file within the version directory.
 /// If exactly one file is found, it's path will be returned. Otherwise an error will be returned.
 #[cfg(target_os = "linux")]
-fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, String> {
+pub fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, GdmanError> {
     let files = get_files(dir_path)?;
 
     if files.len() == 1 {
@@ -381,38 +1602,45 @@ fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, String> {
             return Ok(exe_path);
         }
     }
-    return Err("Cant find Godot executable".to_owned());
+    return Err(GdmanError::ExecutableNotFound(dir_path.clone()));
 }
 
 /// On Windows, the Godot executable is expected to match the directory name.
 /// If this is found, it's path will be returned. Otherwise an error will be returned.
 #[cfg(windows)]
-fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, String> {
-    let dir_name = dir_path.file_name().unwrap();
+pub fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, GdmanError> {
+    let dir_name = dir_path.file_name();
     for file in get_files(dir_path)? {
-        if file.file_name() == dir_name || file.path().file_stem().unwrap() == dir_name {
+        if Some(file.file_name().as_os_str()) == dir_name || file.path().file_stem() == dir_name {
             let exe_path = file.path();
             if !exe_path.ends_with(".zip") {
                 return Ok(exe_path);
             }
         }
     }
-    return Err("Cant find Godot executable".to_owned());
+    return Err(GdmanError::ExecutableNotFound(dir_path.clone()));
 }
 
 /// On MacOS, the executable is expected to be in a consistent, exact location.
 /// If this is found, it will be returned, otherwise an error will be returned.
 #[cfg(target_os = "macos")]
-fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, String> {
+pub fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, GdmanError> {
     // there should be one folder within dir_path.
     // It name depends on the flavour - we dont care which it is, we just need to find it
-    let entries = fs::read_dir(dir_path)
-        .unwrap()
-        .filter(|e| e.as_ref().unwrap().path().is_dir())
-        .flatten()
-        .collect::<Vec<DirEntry>>();
+    let entries = match fs::read_dir(dir_path) {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Error reading version directory {}", dir_path.display()),
+                e,
+            ))
+        }
+        Ok(e) => e,
+    }
+    .flatten()
+    .filter(|e| e.path().is_dir())
+    .collect::<Vec<DirEntry>>();
 
-    let err = Err("Cant find Godot executable".to_owned());
+    let err = Err(GdmanError::ExecutableNotFound(dir_path.clone()));
     if entries.len() != 1 {
         return err;
     }
@@ -427,160 +1655,591 @@ fn get_godot_exe_path(dir_path: &PathBuf) -> Result<PathBuf, String> {
     };
 }
 
-fn get_files(dir_path: &PathBuf) -> Result<Vec<DirEntry>, String> {
+/// Gets the total size in bytes of all files within the directory, including subdirectories
+pub fn get_dir_size(dir_path: &Path) -> u64 {
+    let entries = match fs::read_dir(dir_path) {
+        Err(_) => return 0,
+        Ok(e) => e,
+    };
+    return entries
+        .flatten()
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => get_dir_size(&e.path()),
+            Ok(t) if t.is_file() => e.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum();
+}
+
+fn get_files(dir_path: &PathBuf) -> Result<Vec<DirEntry>, GdmanError> {
     return Ok(match fs::read_dir(dir_path) {
-        Err(err) => return Err(err.to_string().to_owned()),
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Error reading version directory {}", dir_path.display()),
+                e,
+            ))
+        }
         Ok(e) => e,
     }
     .flatten()
-    .filter(|e| e.metadata().unwrap().is_file())
+    .filter(|e| e.metadata().is_ok_and(|m| m.is_file()))
     .collect::<Vec<DirEntry>>());
 }
 
-async fn download_file(
+/// Downloads the file at the specified URL to the output file path.
+/// The file is downloaded to a .part file first, so if the download is interrupted it can be
+/// resumed next time, as long as the server supports range requests and the file hasn't changed.
+/// Transient failures are retried with exponential backoff.
+pub async fn download_file(
     client: &reqwest::Client,
-    url: Url,
-    out_file_path: &PathBuf,
-) -> Result<File, String> {
-    let download_size = {
-        let resp = match client.head(url.as_str()).send().await {
-            Err(e) => return Err(e.to_string()),
-            Ok(d) => d,
-        };
-        if resp.status().is_success() {
-            resp.headers() // Gives us the HeaderMap
-                .get(header::CONTENT_LENGTH) // Gives us an Option containing the HeaderValue
-                .and_then(|ct_len| ct_len.to_str().ok()) // Unwraps the Option as &str
-                .and_then(|ct_len| ct_len.parse().ok()) // Parses the Option as u64
-                .unwrap_or(0) // Fallback to 0
-        } else {
-            // We return an Error if something goes wrong here
-            return Err(
-                format!("Couldn't download URL: {}. Error: {:?}", url, resp.status(),).into(),
-            );
-        }
+    url: &str,
+    out_file_path: &Path,
+    progress: &dyn ProgressReporter,
+) -> Result<(), GdmanError> {
+    let url = match Url::parse(url) {
+        Err(e) => return Err(GdmanError::Other(format!("Invalid URL {url}\n{e}"))),
+        Ok(u) => u,
     };
 
-    let request = client.get(url);
+    let parent_path = out_file_path.parent().unwrap_or(Path::new(""));
 
-    let progress_bar = ProgressBar::new(download_size);
+    log::trace!("Creating {}", &parent_path.display());
+    if let Err(e) = tokio::fs::create_dir_all(&parent_path).await {
+        return Err(GdmanError::io(
+            format!("Failed to create directory {}", parent_path.display()),
+            e,
+        ));
+    }
 
-    // Set Style to the ProgressBar
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("[{bar:40.cyan/blue}] {bytes}/{total_bytes} - {msg}")
-            .or(Err("Invalid progress bar".to_string()))?
-            .progress_chars("#>-"),
-    );
+    let part_file_path = append_extension(out_file_path, "part");
+    let etag_file_path = append_extension(out_file_path, "part.etag");
 
-    progress_bar.set_message("Download progress");
+    let mut attempt = 1;
 
-    let parent_path = out_file_path.parent().unwrap();
+    loop {
+        match download_part_file(client, &url, &part_file_path, &etag_file_path, progress).await {
+            Ok(()) => break,
+            Err(DownloadError::Fatal(e)) => return Err(e),
+            Err(DownloadError::Transient(e)) if attempt >= DOWNLOAD_ATTEMPTS => {
+                return Err(GdmanError::DownloadFailed {
+                    attempts: attempt,
+                    source: Box::new(e),
+                })
+            }
+            Err(DownloadError::Transient(e)) => {
+                let delay = DOWNLOAD_RETRY_DELAY * 2u32.pow(attempt - 1);
+                log::warn!("{}\nRetrying in {} seconds", e.report(), delay.as_secs());
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
 
-    log::trace!("Creating {}", &parent_path.display());
-    if let Err(e) = tokio::fs::create_dir_all(&parent_path).await {
-        return Err(format!(
-            "Failed to create directory {}\n{}",
-            parent_path.display(),
+    if let Err(e) = fs::rename(&part_file_path, out_file_path) {
+        return Err(GdmanError::io(
+            format!(
+                "Failed to rename {} to {}",
+                part_file_path.display(),
+                out_file_path.display()
+            ),
             e,
         ));
     }
+    let _ = fs::remove_file(&etag_file_path);
+
+    return Ok(());
+}
+
+/// Opens the file, returning it along with the hex-encoded SHA512 hash of its contents
+async fn open_and_hash_file(path: &Path) -> Result<(File, String), GdmanError> {
+    let mut file = match File::open(path).await {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to open file '{}'", path.display()),
+                e,
+            ))
+        }
+        Ok(f) => f,
+    };
+
+    let sha512 = hash_file(&mut file).await?;
+
+    return Ok((file, sha512));
+}
+
+enum DownloadError {
+    /// The download may succeed if tried again, e.g. a dropped connection
+    Transient(GdmanError),
+    /// The download will not succeed if tried again, e.g. the file doesn't exist
+    Fatal(GdmanError),
+}
+
+/// Sends the request, treating a failure to get any response as transient
+async fn send_download_request(
+    request: reqwest::RequestBuilder,
+    url: &Url,
+) -> Result<reqwest::Response, DownloadError> {
+    return match request.send().await {
+        Err(e) => Err(DownloadError::Transient(GdmanError::Network {
+            url: url.to_string(),
+            source: e,
+        })),
+        Ok(r) => Ok(r),
+    };
+}
+
+/// Downloads the remainder of the part file, or all of it if the download can't be resumed
+async fn download_part_file(
+    client: &reqwest::Client,
+    url: &Url,
+    part_file_path: &Path,
+    etag_file_path: &Path,
+    progress: &dyn ProgressReporter,
+) -> Result<(), DownloadError> {
+    // We can only resume if we know the part file came from the same version of the file,
+    // which the server checks by comparing the ETag sent in If-Range with the file's.
+    // Everything else is taken from the response, as some servers refuse HEAD requests.
+    let part_size = fs::metadata(part_file_path).map(|m| m.len()).unwrap_or(0);
+    let part_etag = fs::read_to_string(etag_file_path).ok();
+    let resume_from = match &part_etag {
+        Some(_) if part_size > 0 => part_size,
+        _ => 0,
+    };
+
+    let mut request = client.get(url.as_str());
+    if resume_from > 0 {
+        request = request
+            .header(header::RANGE, format!("bytes={resume_from}-"))
+            .header(header::IF_RANGE, part_etag.unwrap());
+    }
+
+    log::trace!("Downloading zip file contents");
+    // Do the actual request to download the file
+    let mut download = send_download_request(request, url).await?;
+
+    // The server can't send the rest of the file from where the part file ends,
+    // either because it's already all there, or because the part file is larger
+    // than the file and so can't be from the same download
+    if resume_from > 0 && download.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        if get_content_range_total(&download) == Some(resume_from) {
+            log::trace!("{} already fully downloaded", part_file_path.display());
+            return Ok(());
+        }
+        log::info!("Can't resume download, starting over");
+        download = send_download_request(client.get(url.as_str()), url).await?;
+    }
+
+    if !download.status().is_success() {
+        return Err(get_download_error(url, download.status()));
+    }
+
+    // If the file has changed since the part file was downloaded, or the server doesn't
+    // support ranges, it sends the whole file, so we need to start over
+    let resumed = download.status() == StatusCode::PARTIAL_CONTENT;
+
+    let download_size = match resumed {
+        true => get_content_range_total(&download).unwrap_or(0),
+        false => download.content_length().unwrap_or(0),
+    };
+
+    if resumed {
+        log::info!("Resuming download from {}", HumanBytes(resume_from));
+    } else {
+        let etag = download
+            .headers()
+            .get(header::ETAG)
+            .and_then(|e| e.to_str().ok());
+        match etag {
+            None => {
+                let _ = fs::remove_file(etag_file_path);
+            }
+            Some(etag) => {
+                if let Err(e) = fs::write(etag_file_path, etag) {
+                    log::warn!("Failed to write {}\n{e}", etag_file_path.display());
+                }
+            }
+        }
+    }
 
-    log::trace!("Creating {}", &out_file_path.display());
+    log::trace!("Creating {}", &part_file_path.display());
 
     let mut file = match tokio::fs::File::options()
-        .read(true)
         .write(true)
         .create(true)
-        .open(&out_file_path)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part_file_path)
         .await
     {
         Err(e) => {
-            return Err(format!(
-                "Failed to create file '{}'\n{e}",
-                out_file_path.display()
-            ))
+            return Err(DownloadError::Fatal(GdmanError::io(
+                format!("Failed to create file '{}'", part_file_path.display()),
+                e,
+            )))
         }
         Ok(f) => f,
     };
 
-    log::trace!("Downloading zip file contents");
-    // Do the actual request to download the file
-    let mut download = match request.send().await {
-        Err(e) => return Err(e.to_string()),
-        Ok(d) => d,
+    progress.report(ProgressEvent::DownloadStarted {
+        total: download_size,
+        position: if resumed { resume_from } else { 0 },
+    });
+
+    loop {
+        let chunk = match download.chunk().await {
+            Err(e) => {
+                progress.report(ProgressEvent::DownloadFailed);
+                return Err(DownloadError::Transient(GdmanError::Network {
+                    url: url.to_string(),
+                    source: e,
+                }));
+            }
+            Ok(None) => break,
+            Ok(Some(c)) => c,
+        };
+        progress.report(ProgressEvent::Downloaded(chunk.len() as u64));
+        if let Err(e) = file.write_all(&chunk).await {
+            progress.report(ProgressEvent::DownloadFailed);
+            return Err(DownloadError::Fatal(GdmanError::io(
+                "Error writing chunk to file",
+                e,
+            )));
+        }
+    }
+
+    progress.report(ProgressEvent::DownloadFinished);
+
+    if let Err(e) = file.flush().await {
+        return Err(DownloadError::Fatal(GdmanError::io(
+            "Error flushing file",
+            e,
+        )));
+    }
+
+    return Ok(());
+}
+
+/// Gets the size of the whole file from the Content-Range header of a response to
+/// a range request, e.g. 1024 from `bytes 512-1023/1024` or `bytes */1024`
+fn get_content_range_total(response: &reqwest::Response) -> Option<u64> {
+    return response
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|r| r.to_str().ok())
+        .and_then(|r| r.rsplit_once('/'))
+        .and_then(|(_, total)| total.parse().ok());
+}
+
+/// Server errors and rate limiting are worth retrying, other errors aren't
+fn get_download_error(url: &Url, status: StatusCode) -> DownloadError {
+    let message = GdmanError::Http {
+        url: url.to_string(),
+        status,
+        message: None,
     };
+    return match status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        true => DownloadError::Transient(message),
+        false => DownloadError::Fatal(message),
+    };
+}
 
-    while let Some(chunk) = download.chunk().await.or(Err("Error downloading chunk"))? {
-        progress_bar.inc(chunk.len() as u64); // Increase ProgressBar by chunk size
-        file.write(&chunk)
-            .await
-            .or(Err("Error writing chunk to file"))?; // Write chunk to output file
+async fn hash_file(file: &mut File) -> Result<String, GdmanError> {
+    let mut hasher = Sha512::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = match file.read(&mut buffer).await {
+            Err(e) => return Err(GdmanError::io("Error reading downloaded file", e)),
+            Ok(r) => r,
+        };
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    if let Err(e) = file.rewind().await {
+        return Err(GdmanError::io("Error reading downloaded file", e));
+    }
+
+    return Ok(format!("{:x}", hasher.finalize()));
+}
+
+/// Gets the permissions to extract a zip entry with from the unix mode stored in the archive.
+/// Archives created on Windows don't store one, so these fall back to being read-write.
+#[cfg(unix)]
+fn get_entry_mode(unix_permissions: Option<u16>) -> u32 {
+    return match unix_permissions.map(|mode| u32::from(mode) & 0o777) {
+        Some(mode) if mode != 0 => mode,
+        _ => DEFAULT_FILE_MODE,
+    };
+}
+
+/// Makes sure the Godot executable can be executed, in case the archive didn't store its mode
+#[cfg(unix)]
+pub fn ensure_executable(exe_path: &Path) -> Result<(), GdmanError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let permissions = match fs::metadata(exe_path) {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to read {}", exe_path.display()),
+                e,
+            ))
+        }
+        Ok(m) => m.permissions(),
+    };
+
+    if permissions.mode() & 0o111 != 0 {
+        return Ok(());
+    }
+
+    log::trace!("Making {} executable", exe_path.display());
+    return fs::set_permissions(exe_path, fs::Permissions::from_mode(EXECUTABLE_FILE_MODE))
+        .map_err(|e| {
+            GdmanError::io(
+                format!("Failed to make {} executable", exe_path.display()),
+                e,
+            )
+        });
+}
+
+#[cfg(windows)]
+pub fn ensure_executable(_exe_path: &Path) -> Result<(), GdmanError> {
+    return Ok(());
+}
+
+/// Lexically resolves the path of a zip entry (or symlink target) relative to the specified
+/// components of its parent path, which are all relative to the output directory.
+/// Returns None if the path is absolute, or would end up outside of the output directory.
+fn resolve_entry_path(mut components: Vec<String>, path: &str) -> Option<Vec<String>> {
+    // Some archives use Windows separators
+    if path.starts_with(['/', '\\']) {
+        return None;
+    }
+
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop()?;
+            }
+            c if is_unsafe_component(c) => return None,
+            c => components.push(c.to_owned()),
+        }
+    }
+
+    return Some(components);
+}
+
+/// Whether a component of an entry's path (or symlink target) could make it absolute, wherever
+/// in the path it is. On Windows, joining a path with a drive prefix like C: part-way through
+/// replaces everything before it, including the output directory.
+fn is_unsafe_component(component: &str) -> bool {
+    return component.contains(':')
+        || Path::new(component)
+            .components()
+            .any(|c| matches!(c, Component::Prefix(_) | Component::RootDir));
+}
+
+/// Whether the path of an entry, as components relative to the output directory,
+/// goes through any of the symlinks on its way to the entry itself
+fn passes_through_symlink(components: &[String], symlink_paths: &HashSet<Vec<String>>) -> bool {
+    return (1..components.len()).any(|i| symlink_paths.contains(&components[..i]));
+}
+
+/// Resolves the target of a symlink like `resolve_entry_path`, except that it also
+/// returns None if the target goes through another symlink, since it could point anywhere
+fn resolve_symlink_target(
+    mut components: Vec<String>,
+    target: &str,
+    symlink_paths: &HashSet<Vec<String>>,
+) -> Option<Vec<String>> {
+    if target.starts_with(['/', '\\']) {
+        return None;
+    }
+
+    for component in target.split(['/', '\\']) {
+        if component.is_empty() || component == "." {
+            continue;
+        }
+        if is_unsafe_component(component) {
+            return None;
+        }
+        // Ending on a symlink is fine, its own target is checked separately
+        if symlink_paths.contains(&components) {
+            return None;
+        }
+        if component == ".." {
+            components.pop()?;
+        } else {
+            components.push(component.to_owned());
+        }
+    }
+
+    return Some(components);
+}
+
+#[cfg(unix)]
+async fn create_symlink(out_dir: &Path, path: &Path, target: &str) -> Result<(), GdmanError> {
+    if let Some(parent) = path.parent() {
+        if let Err(e) = create_dir_all(parent).await {
+            return Err(GdmanError::io("Failed to create parent directories", e));
+        }
+
+        // The target was checked relative to where the symlink should be, so
+        // make sure that's where it really is on disk
+        let is_within_out_dir = match (parent.canonicalize(), out_dir.canonicalize()) {
+            (Ok(parent), Ok(out_dir)) => parent.starts_with(out_dir),
+            _ => false,
+        };
+        if !is_within_out_dir {
+            return Err(GdmanError::InvalidArchive(format!(
+                "Refusing to create symlink {}, it would be outside {}",
+                path.display(),
+                out_dir.display()
+            )));
+        }
     }
 
-    progress_bar.finish();
+    log::trace!("Creating symlink {} to {target}", path.display());
+
+    return tokio::fs::symlink(target, path).await.map_err(|e| {
+        GdmanError::io(
+            format!("Failed to create symlink {} to {target}", path.display()),
+            e,
+        )
+    });
+}
 
-    file.flush().await.or(Err("Error flushing file"))?;
+/// Creating symlinks on Windows requires elevated permissions, and the archives for
+/// Windows aren't expected to contain any, so they're skipped
+#[cfg(windows)]
+async fn create_symlink(_out_dir: &Path, path: &Path, target: &str) -> Result<(), GdmanError> {
+    log::warn!("Skipping symlink {} to {target}", path.display());
+    return Ok(());
+}
 
-    return Ok(file);
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    return PathBuf::from(path);
 }
 
-async fn unzip_file(file: tokio::fs::File, out_dir: &Path) -> Result<(), String> {
+async fn unzip_file(
+    file: tokio::fs::File,
+    out_dir: &Path,
+    entry_prefix: &str,
+    progress: &dyn ProgressReporter,
+) -> Result<(), GdmanError> {
     log::trace!("Creating zip reader");
     let archive = BufReader::new(file).compat();
-    let mut reader = ZipFileReader::new(archive)
-        .await
-        .or(Err("Error creating zip reader"))?;
+    let mut reader = match ZipFileReader::new(archive).await {
+        Err(e) => {
+            return Err(GdmanError::InvalidArchive(format!(
+                "Error creating zip reader\n{e}"
+            )))
+        }
+        Ok(r) => r,
+    };
 
-    let out_dir_name = out_dir.file_name().unwrap().to_str().unwrap().to_owned() + "/";
     let entry_count = reader.file().entries().len();
-    let progress_bar = ProgressBar::new(entry_count as u64);
-
-    log::trace!("Creating zip progress bar");
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("[{bar:40.cyan/blue}] {pos}/{len} - {msg}")
-            .or(Err("Error creating progress bar for zip progress"))?
-            .progress_chars("#>-"),
-    );
 
-    progress_bar.set_message("Extracting contents from zip");
+    // The sizes in the archive can't be trusted, so this is only a quick check
+    // to fail early, the number of bytes actually extracted is checked as we go
+    let declared_size: u64 = reader
+        .file()
+        .entries()
+        .iter()
+        .map(|e| e.uncompressed_size())
+        .sum();
+    if declared_size > MAX_EXTRACTED_SIZE {
+        return Err(GdmanError::InvalidArchive(format!(
+            "Refusing to extract archive, its contents are {} which exceeds the limit of {}",
+            HumanBytes(declared_size),
+            HumanBytes(MAX_EXTRACTED_SIZE)
+        )));
+    }
+    let mut extracted_size: u64 = 0;
+
+    // Symlinks are only created once everything else has been extracted, so nothing
+    // can be written through one, and are checked against all the other entries
+    let mut entry_paths: Vec<Vec<String>> = Vec::new();
+    let mut symlinks: Vec<(String, Vec<String>, String)> = Vec::new();
+
+    progress.report(ProgressEvent::ExtractStarted {
+        entries: entry_count as u64,
+    });
 
     for index in 0..entry_count {
         log::trace!("Extracting entry {index} of {entry_count}");
-        let entry = reader.file().entries().get(index).unwrap();
-
-        // reduce nesting where the zip contains a folder matching
-        // the parent directory name. Remove the current directory name
-        // from the start of the entry path if it exists.
-        let mut path = out_dir.to_path_buf();
-        match entry
-            .filename()
-            .as_str()
-            .unwrap()
-            .trim_start_matches(out_dir_name.as_str())
-        {
-            "" => (),
-            v => path.push(v),
+        let entry = &reader.file().entries()[index];
+
+        let entry_name = match entry.filename().as_str() {
+            Err(_) => {
+                return Err(GdmanError::InvalidArchive(format!(
+                    "Zip entry {index} has an invalid name"
+                )))
+            }
+            Ok(n) => n.to_owned(),
         };
 
-        let entry_is_dir = entry.dir().unwrap();
+        // Remove the prefix from the start of the entry path if it exists,
+        // to avoid unnecessary nesting within the output directory
+        let entry_components =
+            match resolve_entry_path(Vec::new(), entry_name.trim_start_matches(entry_prefix)) {
+                None => {
+                    return Err(GdmanError::InvalidArchive(format!(
+                        "Refusing to extract zip entry {entry_name}, it would be outside {}",
+                        out_dir.display()
+                    )))
+                }
+                Some(c) => c,
+            };
+        let path = out_dir.join(PathBuf::from_iter(&entry_components));
+        entry_paths.push(entry_components.clone());
+
+        let entry_is_dir = entry_name.ends_with('/');
+        let entry_is_symlink = entry
+            .unix_permissions()
+            .is_some_and(|mode| u32::from(mode) & S_IFMT == S_IFLNK);
+        #[cfg(unix)]
+        let entry_mode = get_entry_mode(entry.unix_permissions());
 
         log::trace!("Creating reader for zip entry {index}");
-        let mut entry_reader = reader
-            .reader_without_entry(index)
-            .await
-            .or(Err("Failed to read ZipEntry"))?;
+        let mut entry_reader = match reader.reader_without_entry(index).await {
+            Err(e) => {
+                return Err(GdmanError::InvalidArchive(format!(
+                    "Failed to read zip entry {entry_name}\n{e}"
+                )))
+            }
+            Ok(r) => r,
+        };
+
+        if entry_is_symlink {
+            let mut target = String::new();
+            if let Err(e) = (&mut entry_reader)
+                .take(MAX_SYMLINK_TARGET_LENGTH)
+                .read_to_string(&mut target)
+                .await
+            {
+                return Err(GdmanError::io(
+                    format!("Failed to read symlink zip entry {entry_name}"),
+                    e,
+                ));
+            }
 
-        if entry_is_dir {
+            symlinks.push((entry_name, entry_components, target));
+        } else if entry_is_dir {
             if !path.exists() {
                 log::trace!(
                     "Creating directory {} for zip entry {index}",
                     path.display()
                 );
-                create_dir_all(&path)
-                    .await
-                    .or(Err("Failed to create extracted directory"))?;
+                if let Err(e) = create_dir_all(&path).await {
+                    return Err(GdmanError::io("Failed to create extracted directory", e));
+                }
             }
         } else {
             let parent = path
@@ -592,9 +2251,9 @@ async fn unzip_file(file: tokio::fs::File, out_dir: &Path) -> Result<(), String>
                     "Creating directory {} for zip entry {index}",
                     parent.display()
                 );
-                create_dir_all(parent)
-                    .await
-                    .or(Err("Failed to create parent directories"))?;
+                if let Err(e) = create_dir_all(parent).await {
+                    return Err(GdmanError::io("Failed to create parent directories", e));
+                }
             }
 
             log::trace!("Creating writer for zip entry {index}");
@@ -602,24 +2261,82 @@ async fn unzip_file(file: tokio::fs::File, out_dir: &Path) -> Result<(), String>
             file_opts.write(true).create_new(true);
 
             #[cfg(unix)]
-            file_opts.mode(755);
-
-            let writer = file_opts
-                .open(&path)
-                .await
-                .expect("Failed to create extracted file");
+            file_opts.mode(entry_mode);
+
+            let writer = match file_opts.open(&path).await {
+                Err(e) => {
+                    return Err(GdmanError::io(
+                        format!("Failed to create extracted file {}", path.display()),
+                        e,
+                    ))
+                }
+                Ok(w) => w,
+            };
 
             log::trace!("Extracting zip entry {index} to {}", path.display());
 
-            futures_lite::io::copy(&mut entry_reader, &mut writer.compat_write())
-                .await
-                .or(Err("Failed to copy to extracted file"))?;
+            // Read one byte more than the remaining limit, so we can tell if it's been exceeded
+            let remaining_size = MAX_EXTRACTED_SIZE - extracted_size;
+            extracted_size += match futures_lite::io::copy(
+                (&mut entry_reader).take(remaining_size + 1),
+                &mut writer.compat_write(),
+            )
+            .await
+            {
+                Err(e) => {
+                    return Err(GdmanError::io(
+                        format!("Failed to extract {}", path.display()),
+                        e,
+                    ))
+                }
+                Ok(size) => size,
+            };
+
+            if extracted_size > MAX_EXTRACTED_SIZE {
+                return Err(GdmanError::InvalidArchive(format!(
+                    "Refusing to extract archive, its contents exceed the limit of {}",
+                    HumanBytes(MAX_EXTRACTED_SIZE)
+                )));
+            }
+
+            progress.report(ProgressEvent::Extracted(1));
+        }
+    }
+
+    let symlink_paths: HashSet<Vec<String>> = symlinks
+        .iter()
+        .map(|(_, components, _)| components.clone())
+        .collect();
+
+    if let Some(entry_components) = entry_paths
+        .iter()
+        .find(|c| passes_through_symlink(c, &symlink_paths))
+    {
+        return Err(GdmanError::InvalidArchive(format!(
+            "Refusing to extract zip entry {}, it's inside a symlink",
+            entry_components.join("/")
+        )));
+    }
 
-            progress_bar.inc(1);
+    for (entry_name, entry_components, target) in &symlinks {
+        // Symlinks can point anywhere, so we only allow those pointing to
+        // somewhere within the output directory
+        let link_dir_components =
+            entry_components[..entry_components.len().saturating_sub(1)].to_vec();
+        if resolve_symlink_target(link_dir_components, target, &symlink_paths).is_none() {
+            return Err(GdmanError::InvalidArchive(format!(
+                "Refusing to extract symlink {entry_name} to {target}, it points outside {}",
+                out_dir.display()
+            )));
         }
+
+        let path = out_dir.join(PathBuf::from_iter(entry_components));
+        create_symlink(out_dir, &path, target).await?;
+
+        progress.report(ProgressEvent::Extracted(1));
     }
 
-    progress_bar.finish();
+    progress.report(ProgressEvent::ExtractFinished);
 
     return Ok(());
 }
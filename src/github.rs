@@ -1,2 +1,3 @@
-mod github_repo;
+pub mod github_repo;
 pub mod godot_repo;
+pub mod releases_cache;
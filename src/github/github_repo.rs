@@ -1,11 +1,18 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
 use regex::Regex;
-use reqwest::header::HeaderMap;
+use reqwest::header::{self, HeaderMap};
+use reqwest::StatusCode;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 
-use crate::gd_semver::parse_semver_version;
+use crate::common::Channel;
+use crate::error::GdmanError;
+use crate::gd_semver::{
+    compare_versions, get_channel, matches_including_pre_releases, parse_semver_version,
+};
+use crate::github::releases_cache::{self, CachedReleases};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Release {
@@ -118,25 +125,256 @@ pub struct Asset {
 // }
 
 const BASE_URL: &str = "https://api.github.com";
+const RELEASES_PER_PAGE: u8 = 100;
+const USER_AGENT: &str = concat!("gdman/", env!("CARGO_PKG_VERSION"));
+/// Environment variables checked (in order) for a token to authenticate with GitHub
+const TOKEN_ENV_VARS: [&str; 2] = ["GDMAN_GITHUB_TOKEN", "GITHUB_TOKEN"];
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
 
-pub async fn get_releases(
-    owner: &str,
-    repo: &str,
+struct ReleasesPage {
+    releases: Vec<Release>,
+    next_page_url: Option<String>,
+    etag: Option<String>,
+}
+
+/// Fetches a page of releases. If an etag is specified and the page hasn't
+/// changed since it was issued, None is returned.
+async fn get_releases_page(
+    url: &str,
+    etag: &Option<String>,
     client: &reqwest::Client,
-) -> Result<Vec<Release>, reqwest::Error> {
-    let mut headers = HeaderMap::with_capacity(3);
-    headers.append("User-Agent", "request".parse().unwrap());
+) -> Result<Option<ReleasesPage>, GdmanError> {
+    let mut headers = HeaderMap::with_capacity(5);
+    headers.append(header::USER_AGENT, USER_AGENT.parse().unwrap());
     headers.append("Accept", "application/vnd.github+json".parse().unwrap());
     headers.append("X-GitHub-Api-Version", "2022-11-28".parse().unwrap());
-
-    let url = format!("{BASE_URL}/repos/{owner}/{repo}/releases");
+    if let Some(etag) = etag.as_ref().and_then(|e| e.parse().ok()) {
+        headers.append(header::IF_NONE_MATCH, etag);
+    }
+    if let Some(token) = get_token() {
+        match format!("Bearer {token}").parse() {
+            Err(_) => log::warn!("Ignoring GitHub token containing invalid characters"),
+            Ok(auth) => {
+                headers.append(header::AUTHORIZATION, auth);
+            }
+        }
+    }
 
     log::trace!("Fetching releases from {url}");
 
-    let request = client.get(url).headers(headers);
-    let releases: Vec<Release> = request.send().await?.json().await?;
+    let response = match client.get(url).headers(headers).send().await {
+        Err(e) => {
+            return Err(GdmanError::Network {
+                url: url.to_owned(),
+                source: e,
+            })
+        }
+        Ok(r) => r,
+    };
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        log::trace!("Releases have not changed since they were cached");
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        return Err(get_error(response).await);
+    }
+
+    let next_page_url = get_next_page_url(response.headers());
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|e| e.to_str().ok())
+        .map(|e| e.to_owned());
+    let releases: Vec<Release> = match response.json().await {
+        Err(e) => {
+            return Err(GdmanError::parse(
+                format!("Error parsing releases from {url}"),
+                e,
+            ))
+        }
+        Ok(r) => r,
+    };
+
+    return Ok(Some(ReleasesPage {
+        releases,
+        next_page_url,
+        etag,
+    }));
+}
+
+/// Reads the token to authenticate with GitHub from the environment, if there is one.
+fn get_token() -> Option<String> {
+    return TOKEN_ENV_VARS.iter().find_map(|var| {
+        std::env::var(var)
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .map(|t| {
+                log::trace!("Authenticating with GitHub using token from {var}");
+                t.trim().to_owned()
+            })
+    });
+}
+
+/// Builds the error for an unsuccessful response, including when the
+/// rate limit resets if that's why the request failed.
+async fn get_error(response: reqwest::Response) -> GdmanError {
+    let status = response.status();
+    let headers = response.headers();
+
+    let remaining = headers
+        .get("X-RateLimit-Remaining")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let reset = headers
+        .get("X-RateLimit-Reset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok())
+        .and_then(|v| DateTime::from_timestamp(v, 0));
+
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && remaining == Some(0));
+
+    if rate_limited {
+        return GdmanError::RateLimited {
+            resets_at: reset.map(|r| r.with_timezone(&Local)),
+            token_env_var: match get_token() {
+                None => Some(TOKEN_ENV_VARS[0]),
+                Some(_) => None,
+            },
+        };
+    }
 
-    return Ok(releases);
+    let url = response.url().to_string();
+    let message = response
+        .json::<ErrorResponse>()
+        .await
+        .ok()
+        .map(|e| e.message);
+
+    return GdmanError::Http {
+        url,
+        status,
+        message,
+    };
+}
+
+/// Gets the releases from the cache, revalidating them with GitHub if they've expired.
+/// If the releases have changed, the cache is reset to contain the first page of releases.
+async fn get_cached_releases(
+    owner: &str,
+    repo: &str,
+    client: &reqwest::Client,
+) -> Result<CachedReleases, GdmanError> {
+    let cached = releases_cache::load(owner, repo);
+
+    if let Some(cached) = cached {
+        if releases_cache::is_offline() || !cached.is_expired() {
+            return Ok(cached);
+        }
+        log::trace!("Cached releases have expired");
+        return fetch_first_page(owner, repo, Some(cached), client).await;
+    }
+
+    if releases_cache::is_offline() {
+        return Err(GdmanError::Offline(format!(
+            "No cached releases found for {owner}/{repo}, unable to fetch them while offline"
+        )));
+    }
+
+    return fetch_first_page(owner, repo, None, client).await;
+}
+
+async fn fetch_first_page(
+    owner: &str,
+    repo: &str,
+    cached: Option<CachedReleases>,
+    client: &reqwest::Client,
+) -> Result<CachedReleases, GdmanError> {
+    let url = format!("{BASE_URL}/repos/{owner}/{repo}/releases?per_page={RELEASES_PER_PAGE}");
+    let etag = cached.as_ref().and_then(|c| c.etag.clone());
+
+    let cached = match get_releases_page(&url, &etag, client).await? {
+        None => match cached {
+            None => {
+                return Err(GdmanError::Other(format!(
+                    "Unexpected response fetching releases from {url}"
+                )))
+            }
+            Some(mut cached) => {
+                cached.fetched_at = Utc::now();
+                cached
+            }
+        },
+        Some(page) => CachedReleases {
+            fetched_at: Utc::now(),
+            etag: page.etag,
+            releases: page.releases,
+            next_page_url: page.next_page_url,
+        },
+    };
+
+    releases_cache::save(owner, repo, &cached)?;
+
+    return Ok(cached);
+}
+
+/// Fetches the next page of releases that aren't yet cached, adding them to the cache.
+/// Returns false if there are no more pages to fetch, or if offline.
+async fn fetch_next_page(
+    owner: &str,
+    repo: &str,
+    cached: &mut CachedReleases,
+    client: &reqwest::Client,
+) -> Result<bool, GdmanError> {
+    let url = match &cached.next_page_url {
+        None => return Ok(false),
+        Some(u) => u.clone(),
+    };
+
+    if releases_cache::is_offline() {
+        log::trace!("Offline, unable to fetch more releases");
+        return Ok(false);
+    }
+
+    let page = match get_releases_page(&url, &None, client).await? {
+        None => {
+            return Err(GdmanError::Other(format!(
+                "Unexpected response fetching releases from {url}"
+            )))
+        }
+        Some(p) => p,
+    };
+
+    cached.releases.extend(page.releases);
+    cached.next_page_url = page.next_page_url;
+
+    releases_cache::save(owner, repo, cached)?;
+
+    return Ok(true);
+}
+
+/// Extracts the URL of the next page of results from the `Link` response header, e.g.
+/// `<https://api.github.com/repositories/1/releases?page=2>; rel="next", <...>; rel="last"`
+fn get_next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get(header::LINK)?.to_str().ok()?;
+    return link.split(',').find_map(|part| {
+        let mut segments = part.split(';');
+        let url = segments
+            .next()?
+            .trim()
+            .trim_start_matches('<')
+            .trim_end_matches('>');
+        match segments.any(|s| s.trim() == "rel=\"next\"") {
+            true => Some(url.to_owned()),
+            false => None,
+        }
+    });
 }
 
 pub async fn find_release(
@@ -144,28 +382,53 @@ pub async fn find_release(
     repo: &str,
     version_exact: &Option<semver::Version>,
     version_like: &Option<semver::VersionReq>,
+    channel: &Channel,
     client: &reqwest::Client,
-) -> Result<Release, String> {
-    let releases = match get_releases(owner, repo, client).await {
-        Err(e) => return Err(e.to_string()),
-        Ok(r) => r,
-    };
+) -> Result<Release, GdmanError> {
+    let release = match version_exact {
+        // An exact version can't be bettered, so we walk through the pages until
+        // we find one containing it rather than fetching them all
+        Some(_) => {
+            let mut cached = get_cached_releases(owner, repo, client).await?;
+            let mut searched = 0;
+            loop {
+                let release = select_release(
+                    &cached.releases[searched..],
+                    version_exact,
+                    version_like,
+                    channel,
+                );
 
-    let release: Option<Release>;
+                if release.is_some() {
+                    break release;
+                }
 
-    // If the caller has specified an exact version, we need to look through all
-    // releases, parse the semver from the tag, and check if it's equal to the
-    // specified exact version
-    if let Some(version_exact) = version_exact {
-        release = find_exact_release_version(&releases, version_exact);
-    } else if let Some(version_like) = version_like {
-        release = find_latest_release(&releases, &Some(version_like));
-    } else {
-        release = find_latest_release(&releases, &None);
-    }
+                searched = cached.releases.len();
+
+                if !fetch_next_page(owner, repo, &mut cached, client).await? {
+                    break None;
+                }
+
+                log::trace!("No matching release found yet, checking next page of releases");
+            }
+        }
+        // Releases are returned newest first, which isn't the same as latest version first
+        // (e.g. a patch to an older version), so the latest match could be on any page
+        None => select_release(
+            &get_releases(owner, repo, client).await?,
+            version_exact,
+            version_like,
+            channel,
+        ),
+    };
 
     return match release {
-        None => Err("No matching release found".to_owned()),
+        None if releases_cache::is_offline() => Err(GdmanError::VersionNotFound(
+            "No matching release found in the cache while offline".to_owned(),
+        )),
+        None => Err(GdmanError::VersionNotFound(
+            "No matching release found".to_owned(),
+        )),
         Some(release) => {
             log::info!("Found release with version {}", release.tag_name);
             return Ok(release);
@@ -173,49 +436,128 @@ pub async fn find_release(
     };
 }
 
-pub async fn find_release_with_asset(
+/// Finds the release satisfying the specified version among the releases, being the exact version
+/// if specified, otherwise the latest version matching the version requirement (if specified)
+/// which is at least as stable as the specified channel.
+pub fn select_release(
+    releases: &[Release],
+    version_exact: &Option<semver::Version>,
+    version_like: &Option<semver::VersionReq>,
+    channel: &Channel,
+) -> Option<Release> {
+    // If the caller has specified an exact version, we need to look through all
+    // releases, parse the semver from the tag, and check if it's equal to the
+    // specified exact version
+    if let Some(version_exact) = version_exact {
+        return find_exact_release_version(releases, version_exact);
+    }
+    return find_latest_release(releases, &version_like.as_ref(), channel);
+}
+
+/// Moves the first asset with a name matching all of the patterns to the front of the release's
+/// assets, so it can be found by callers. Fails if the release has no matching asset.
+pub fn select_asset(release: &mut Release, asset_name_like: Vec<String>) -> Result<(), GdmanError> {
+    log::trace!("Finding release asset");
+
+    let name_checks = parse_asset_name_checks(asset_name_like);
+
+    if !move_matching_asset_first(release, &name_checks) {
+        return Err(GdmanError::VersionNotFound(format!(
+            "No assets found for release {}",
+            release.tag_name
+        )));
+    }
+
+    log::info!("Found release asset {}", release.assets[0].name);
+
+    return Ok(());
+}
+
+/// Fetches every release published to the repo, or every cached release when offline
+pub async fn get_releases(
     owner: &str,
     repo: &str,
+    client: &reqwest::Client,
+) -> Result<Vec<Release>, GdmanError> {
+    let mut cached = get_cached_releases(owner, repo, client).await?;
+
+    while fetch_next_page(owner, repo, &mut cached, client).await? {}
+
+    return Ok(cached.releases);
+}
+
+/// Filters the releases down to those matching the specified version which have a matching asset,
+/// ordered from the latest version to the oldest. As with select_asset, the matching asset
+/// is moved to be the first asset of each release.
+pub fn filter_releases_with_asset(
+    releases: Vec<Release>,
     version_exact: &Option<semver::Version>,
     version_like: &Option<semver::VersionReq>,
+    channel: &Channel,
     asset_name_like: Vec<String>,
-    client: &reqwest::Client,
-) -> Result<Release, String> {
-    let mut release = find_release(owner, repo, version_exact, version_like, client).await?;
-
-    log::trace!("Finding release asset");
+) -> Vec<Release> {
+    let name_checks = parse_asset_name_checks(asset_name_like);
 
-    let name_checks = asset_name_like
-        .into_iter()
-        .map(|cur| Regex::new(&cur).unwrap())
-        .into_iter();
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for mut release in releases {
+        let version =
+            match parse_semver_version(&release.tag_name, &Some(vec!["stable".to_owned()])) {
+                Err(_) => {
+                    log::trace!(
+                        "Release tag {} does not indicate a valid version. Skipping",
+                        &release.tag_name
+                    );
+                    continue;
+                }
+                Ok(v) => v,
+            };
 
-    let assets: Vec<Asset> = release
-        .assets
-        .into_iter()
-        .filter(|a| name_checks.clone().all(|n| n.is_match(&a.name)))
-        .collect();
+        let version_matches = match (version_exact, version_like) {
+            (Some(exact), _) => version == *exact,
+            (None, Some(like)) => {
+                get_channel(&version) >= *channel && matches_including_pre_releases(like, &version)
+            }
+            (None, None) => get_channel(&version) >= *channel,
+        };
 
-    if assets.is_empty() {
-        return Err(format!(
-            "No assets found for release {} repo {repo}",
-            release.tag_name
-        )
-        .to_owned());
+        if version_matches && move_matching_asset_first(&mut release, &name_checks) {
+            candidates.push(Candidate { release, version });
+        }
     }
 
-    let asset = assets.first().unwrap().clone();
+    candidates.sort_by(|a, b| compare_versions(&b.version, &a.version));
 
-    log::info!("Found release asset {}", asset.name);
+    return candidates.into_iter().map(|c| c.release).collect();
+}
 
-    release.assets = Vec::new();
-    release.assets.push(asset);
+fn parse_asset_name_checks(asset_name_like: Vec<String>) -> Vec<Regex> {
+    return asset_name_like
+        .into_iter()
+        .map(|cur| Regex::new(&cur).unwrap())
+        .collect();
+}
+
+/// Moves the first asset matching all of the name checks to the front of the release assets.
+/// The rest of the assets are kept since they may contain supporting files, such as checksums.
+/// Returns false if the release has no matching asset.
+fn move_matching_asset_first(release: &mut Release, name_checks: &[Regex]) -> bool {
+    let asset_index = match release
+        .assets
+        .iter()
+        .position(|a| name_checks.iter().all(|n| n.is_match(&a.name)))
+    {
+        None => return false,
+        Some(i) => i,
+    };
 
-    return Ok(release);
+    let asset = release.assets.remove(asset_index);
+    release.assets.insert(0, asset);
+
+    return true;
 }
 
 fn find_exact_release_version(
-    releases: &Vec<Release>,
+    releases: &[Release],
     version_exact: &semver::Version,
 ) -> Option<Release> {
     log::trace!("Finding release matching exact version {version_exact}");
@@ -243,13 +585,16 @@ struct Candidate {
     version: semver::Version,
 }
 
+/// Finds the latest release matching the version requirement (if specified),
+/// considering only releases which are at least as stable as the specified channel.
 fn find_latest_release(
     releases: &[Release],
     version_like: &Option<&semver::VersionReq>,
+    channel: &Channel,
 ) -> Option<Release> {
     match version_like {
-        Some(v) => log::trace!("Finding release matching version {v}"),
-        None => log::trace!("Finding latest version"),
+        Some(v) => log::trace!("Finding {channel} release matching version {v}"),
+        None => log::trace!("Finding latest {channel} version"),
     }
 
     let mut candidate: Option<Candidate> = None;
@@ -266,9 +611,15 @@ fn find_latest_release(
             Ok(v) => v,
         };
 
-        if version_like.is_none() || version_like.unwrap().matches(&release_version) {
+        if get_channel(&release_version) < *channel {
+            continue;
+        }
+
+        if version_like.is_none()
+            || matches_including_pre_releases(version_like.unwrap(), &release_version)
+        {
             if let Some(c) = candidate.borrow() {
-                if release_version > c.version {
+                if compare_versions(&release_version, &c.version) == Ordering::Greater {
                     candidate = Some(Candidate {
                         release: release.clone(),
                         version: release_version,
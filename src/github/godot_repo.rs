@@ -1,11 +1,18 @@
+use std::fs;
+
 use crate::{
-    common::{Architecture, Flavour, Platform},
+    common::{Architecture, Channel, Flavour, Platform},
+    error::GdmanError,
     gd_semver::parse_semver_version,
+    gdman,
     github::github_repo as gh,
+    progress::NoProgress,
+    release_source::{self, ReleaseSource},
 };
 
-const OWNER: &str = "godotengine";
 const REPO: &str = "godot";
+const BUILDS_REPO: &str = "godot-builds";
+const CHECKSUMS_ASSET_NAME: &str = "SHA512-SUMS.txt";
 
 pub async fn find_release_with_asset(
     version_exact: &Option<semver::Version>,
@@ -13,28 +20,178 @@ pub async fn find_release_with_asset(
     platform: &Platform,
     architecture: &Architecture,
     flavour: &Flavour,
+    channel: &Channel,
     client: &reqwest::Client,
-) -> Result<gh::Release, String> {
+) -> Result<gh::Release, GdmanError> {
     let mut asset_name_checks: Vec<String> = Vec::new();
 
     log::trace!("Determining checks to identify target release");
 
-    match generate_asset_name(platform, architecture, flavour) {
-        Ok(asset_name) => asset_name_checks.push(asset_name),
-        Err(err) => return Err(err),
-    }
+    asset_name_checks.push(generate_asset_name(platform, architecture, flavour)?);
 
-    return gh::find_release_with_asset(
-        OWNER,
-        REPO,
+    return find_release_matching_asset(
         version_exact,
         version_like,
+        channel,
         asset_name_checks,
         client,
     )
     .await;
 }
 
+/// Finds every release matching the specified version that has an asset for the
+/// specified platform, architecture and flavour, ordered from latest to oldest.
+pub async fn find_releases_with_asset(
+    version_exact: &Option<semver::Version>,
+    version_like: &Option<semver::VersionReq>,
+    platform: &Platform,
+    architecture: &Architecture,
+    flavour: &Flavour,
+    channel: &Channel,
+    client: &reqwest::Client,
+) -> Result<Vec<gh::Release>, GdmanError> {
+    let asset_name = generate_asset_name(platform, architecture, flavour)?;
+
+    let releases = release_source::from_config()?
+        .get_releases(get_repo(version_exact, channel), client)
+        .await?;
+
+    return Ok(gh::filter_releases_with_asset(
+        releases,
+        version_exact,
+        version_like,
+        channel,
+        vec![asset_name],
+    ));
+}
+
+/// Finds the release matching the specified version, where the first asset
+/// of the returned release is the export templates for the specified flavour.
+pub async fn find_release_with_templates(
+    version_exact: &Option<semver::Version>,
+    version_like: &Option<semver::VersionReq>,
+    flavour: &Flavour,
+    channel: &Channel,
+    client: &reqwest::Client,
+) -> Result<gh::Release, GdmanError> {
+    return find_release_matching_asset(
+        version_exact,
+        version_like,
+        channel,
+        vec![generate_templates_asset_name(flavour)],
+        client,
+    )
+    .await;
+}
+
+/// Finds the release matching the specified version using the configured release source,
+/// moving the asset matching the names to be the first asset of the release
+async fn find_release_matching_asset(
+    version_exact: &Option<semver::Version>,
+    version_like: &Option<semver::VersionReq>,
+    channel: &Channel,
+    asset_name_like: Vec<String>,
+    client: &reqwest::Client,
+) -> Result<gh::Release, GdmanError> {
+    let mut release = release_source::from_config()?
+        .find_release(
+            get_repo(version_exact, channel),
+            version_exact,
+            version_like,
+            channel,
+            client,
+        )
+        .await?;
+
+    gh::select_asset(&mut release, asset_name_like)?;
+
+    return Ok(release);
+}
+
+/// Pre-releases are only published to the godot-builds repo, which contains the stable
+/// releases too, but the main godot repo is preferred when only stable releases are wanted.
+fn get_repo(version_exact: &Option<semver::Version>, channel: &Channel) -> &'static str {
+    let exact_pre_release = version_exact.as_ref().is_some_and(|v| !v.pre.is_empty());
+    return match *channel != Channel::Stable || exact_pre_release {
+        true => BUILDS_REPO,
+        false => REPO,
+    };
+}
+
+/// Looks up the SHA512 checksum of the specified asset in the `SHA512-SUMS.txt`
+/// file that Godot publishes alongside the other assets of each release.
+pub async fn get_asset_checksum(
+    release: &gh::Release,
+    asset_name: &str,
+    client: &reqwest::Client,
+) -> Result<String, GdmanError> {
+    let checksums_asset = match release
+        .assets
+        .iter()
+        .find(|a| a.name == CHECKSUMS_ASSET_NAME)
+    {
+        None => {
+            return Err(GdmanError::ChecksumUnavailable(format!(
+                "Release {} does not publish {CHECKSUMS_ASSET_NAME}, use --skip-verify to install without verifying",
+                release.tag_name
+            )))
+        }
+        Some(a) => a,
+    };
+
+    log::trace!("Fetching checksums from {}", checksums_asset.url);
+
+    // The checksums are downloaded alongside the archive they're for, with the names of the
+    // release and asset so they're not mixed up with those fetched for other downloads,
+    // including those of the same release running at the same time
+    let checksums_path = gdman::get_downloads_dir()?.join(format!(
+        "{}-{asset_name}-{CHECKSUMS_ASSET_NAME}",
+        release.tag_name
+    ));
+
+    release_source::from_config()?
+        .fetch_asset(checksums_asset, &checksums_path, client, &NoProgress)
+        .await?;
+
+    let checksums = fs::read_to_string(&checksums_path);
+    if let Err(e) = fs::remove_file(&checksums_path) {
+        log::warn!("Failed to delete {}\n{e}", checksums_path.display());
+    }
+    let checksums = match checksums {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to read {}", checksums_path.display()),
+                e,
+            ))
+        }
+        Ok(c) => c,
+    };
+
+    return match parse_checksum(&checksums, asset_name) {
+        None => Err(GdmanError::ChecksumUnavailable(format!(
+            "No checksum for {asset_name} found in {CHECKSUMS_ASSET_NAME}"
+        ))),
+        Some(checksum) => {
+            log::trace!("Found checksum {checksum} for {asset_name}");
+            Ok(checksum)
+        }
+    };
+}
+
+/// Each line of the checksums file takes the form `<checksum>  <file name>`,
+/// where the file name may be prefixed with `*` to indicate binary mode.
+fn parse_checksum(checksums: &str, asset_name: &str) -> Option<String> {
+    return checksums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let checksum = parts.next()?;
+        let file_name = parts.next()?.trim_start_matches('*');
+        match file_name == asset_name {
+            true => Some(checksum.to_lowercase()),
+            false => None,
+        }
+    });
+}
+
 pub struct GodotVersionNameParts {
     pub version_string: String,
     pub version: semver::Version,
@@ -52,12 +209,12 @@ impl Clone for GodotVersionNameParts {
             version_name: self.version_name.clone(),
             platform: self.platform.clone(),
             architecture: self.architecture.clone(),
-            flavour: self.flavour.clone(),
+            flavour: self.flavour,
         }
     }
 }
 
-pub fn parse_version_name(version_name: &str) -> Result<GodotVersionNameParts, String> {
+pub fn parse_version_name(version_name: &str) -> Result<GodotVersionNameParts, GdmanError> {
     log::trace!("Parsing version name {version_name}");
 
     let (version_string, version) = parse_version_from_version_name(version_name)?;
@@ -87,13 +244,13 @@ pub fn parse_version_name(version_name: &str) -> Result<GodotVersionNameParts, S
 
 fn parse_version_from_version_name(
     version_name: &str,
-) -> Result<(String, semver::Version), String> {
+) -> Result<(String, semver::Version), GdmanError> {
     let start = match version_name.find("_v") {
-        None => return Err("Invalid version name".to_owned()),
+        None => return Err(GdmanError::InvalidInput("Invalid version name".to_owned())),
         Some(s) => s,
     };
     let end = match version_name[start + 1..].find("_") {
-        None => return Err("Invalid version name".to_owned()),
+        None => return Err(GdmanError::InvalidInput("Invalid version name".to_owned())),
         Some(s) => s,
     };
 
@@ -106,14 +263,14 @@ fn parse_version_from_version_name(
     return Ok((version_string, semver_version));
 }
 
-fn parse_flavour_from_version_name(version_name: &str) -> Result<Flavour, String> {
+fn parse_flavour_from_version_name(version_name: &str) -> Result<Flavour, GdmanError> {
     return match version_name.contains("mono") {
         true => Ok(Flavour::Mono),
         false => Ok(Flavour::Standard),
     };
 }
 
-fn parse_platform_from_version_name(version_name: &str) -> Result<Platform, String> {
+fn parse_platform_from_version_name(version_name: &str) -> Result<Platform, GdmanError> {
     if version_name.contains("win32") || version_name.contains("win64") {
         return Ok(Platform::Windows);
     }
@@ -123,13 +280,13 @@ fn parse_platform_from_version_name(version_name: &str) -> Result<Platform, Stri
     if version_name.contains("linux") {
         return Ok(Platform::Linux);
     }
-    return Err("Invalid version name".to_owned());
+    return Err(GdmanError::InvalidInput("Invalid version name".to_owned()));
 }
 
 fn parse_architecture_from_version_name(
     version_name: &str,
     platform: &Platform,
-) -> Result<Architecture, String> {
+) -> Result<Architecture, GdmanError> {
     match platform {
         #[cfg(target_os = "macos")]
         Platform::MacOS => return Ok(Architecture::Universal),
@@ -141,7 +298,7 @@ fn parse_architecture_from_version_name(
             if version_name.contains("win64") {
                 return Ok(Architecture::X64);
             }
-            return Err("Invalid version name".to_owned());
+            return Err(GdmanError::InvalidInput("Invalid version name".to_owned()));
         }
         #[cfg(target_os = "linux")]
         Platform::Linux => {
@@ -157,9 +314,13 @@ fn parse_architecture_from_version_name(
             if version_name.contains("x86_32") {
                 return Ok(Architecture::X86);
             }
-            return Err("Invalid version name".to_owned());
+            return Err(GdmanError::InvalidInput("Invalid version name".to_owned()));
+        }
+        _ => {
+            return Err(GdmanError::InvalidInput(format!(
+                "Invalid platform {platform}"
+            )))
         }
-        _ => return Err(format!("Invalid platform {platform}")),
     }
 }
 
@@ -171,11 +332,11 @@ fn generate_asset_name(
     platform: &Platform,
     architecture: &Architecture,
     flavour: &Flavour,
-) -> Result<String, String> {
+) -> Result<String, GdmanError> {
     let mut parts: Vec<&str> = Vec::new();
 
     if *flavour == Flavour::Mono {
-        parts.push(&"mono_");
+        parts.push("mono_");
     }
 
     match platform {
@@ -210,8 +371,21 @@ fn generate_asset_name(
         Platform::MacOS => {
             parts.push("macos.universal");
         }
-        _ => return Err(format!("Invalid platform {platform}")),
+        _ => {
+            return Err(GdmanError::InvalidInput(format!(
+                "Invalid platform {platform}"
+            )))
+        }
     }
 
     return Ok(parts.join(""));
 }
+
+// Generates a pattern matching the name of the export templates asset, e.g.
+// Godot_v4.2.1-stable_export_templates.tpz or Godot_v4.2.1-stable_mono_export_templates.tpz
+fn generate_templates_asset_name(flavour: &Flavour) -> String {
+    return match flavour {
+        Flavour::Mono => r"^Godot_v[^_]+_mono_export_templates\.tpz$".to_owned(),
+        Flavour::Standard => r"^Godot_v[^_]+_export_templates\.tpz$".to_owned(),
+    };
+}
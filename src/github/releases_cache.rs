@@ -0,0 +1,145 @@
+/*
+    Local cache of the releases fetched from GitHub, to avoid hitting the
+    API rate limit and to allow versions to be resolved while offline.
+    The pages of releases are cached as they're fetched, along with the
+    link to the next page, so the cache may not contain every release.
+*/
+
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{self, ConfigKey},
+    error::GdmanError,
+    gdman,
+    github::github_repo::Release,
+};
+
+const DEFAULT_TTL_SECONDS: u32 = 60 * 60;
+
+static CACHE_OPTIONS: OnceLock<CacheOptions> = OnceLock::new();
+
+struct CacheOptions {
+    ttl: TimeDelta,
+    offline: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedReleases {
+    pub fetched_at: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub releases: Vec<Release>,
+    pub next_page_url: Option<String>,
+}
+
+impl CachedReleases {
+    pub fn is_expired(&self) -> bool {
+        return Utc::now() - self.fetched_at > get_options().ttl;
+    }
+}
+
+/// Sets up the cache options. The time-to-live (in seconds) can be overridden
+/// via the cache-ttl setting.
+pub fn init(offline: bool) {
+    let ttl_seconds = match config::resolve::<u32>(ConfigKey::CacheTtl, None, None) {
+        Err(e) => {
+            log::warn!("{}, using default of {DEFAULT_TTL_SECONDS}", e.report());
+            DEFAULT_TTL_SECONDS
+        }
+        Ok(t) => t.unwrap_or(DEFAULT_TTL_SECONDS),
+    };
+
+    let _ = CACHE_OPTIONS.set(CacheOptions {
+        ttl: TimeDelta::seconds(ttl_seconds.into()),
+        offline,
+    });
+}
+
+/// When offline, releases are only resolved from the cache, and nothing is downloaded
+pub fn is_offline() -> bool {
+    return get_options().offline;
+}
+
+pub fn load(owner: &str, repo: &str) -> Option<CachedReleases> {
+    let path = get_cache_file_path(owner, repo).ok()?;
+
+    let content = match fs::read_to_string(&path) {
+        Err(_) => {
+            log::trace!("No cached releases at {}", path.display());
+            return None;
+        }
+        Ok(c) => c,
+    };
+
+    return match serde_json::from_str(&content) {
+        Err(e) => {
+            log::warn!("Ignoring invalid releases cache {}\n{e}", path.display());
+            None
+        }
+        Ok(c) => {
+            log::trace!("Loaded cached releases from {}", path.display());
+            Some(c)
+        }
+    };
+}
+
+pub fn save(owner: &str, repo: &str, cached: &CachedReleases) -> Result<(), GdmanError> {
+    let path = get_cache_file_path(owner, repo)?;
+
+    if let Some(parent) = path.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            return Err(GdmanError::io(
+                format!("Failed to create cache directory {}", parent.display()),
+                e,
+            ));
+        }
+    }
+
+    let content = match serde_json::to_string(cached) {
+        Err(e) => {
+            return Err(GdmanError::Other(format!(
+                "Error serializing releases cache\n{e}"
+            )))
+        }
+        Ok(c) => c,
+    };
+
+    log::trace!("Saving cached releases to {}", path.display());
+
+    return fs::write(&path, content)
+        .map_err(|e| GdmanError::io(format!("Failed to write cache file {}", path.display()), e));
+}
+
+pub fn clear() -> Result<(), GdmanError> {
+    let dir = get_cache_dir()?;
+    if !dir.exists() {
+        log::trace!("Cache directory {} does not exist", dir.display());
+        return Ok(());
+    }
+    return fs::remove_dir_all(&dir).map_err(|e| {
+        GdmanError::io(
+            format!("Failed to delete cache directory {}", dir.display()),
+            e,
+        )
+    });
+}
+
+pub fn get_cache_dir() -> Result<PathBuf, GdmanError> {
+    return Ok(gdman::get_home_dir()?.join("cache"));
+}
+
+fn get_cache_file_path(owner: &str, repo: &str) -> Result<PathBuf, GdmanError> {
+    return Ok(get_cache_dir()?
+        .join("releases")
+        .join(owner)
+        .join(format!("{repo}.json")));
+}
+
+fn get_options() -> &'static CacheOptions {
+    return CACHE_OPTIONS.get_or_init(|| CacheOptions {
+        ttl: TimeDelta::seconds(DEFAULT_TTL_SECONDS.into()),
+        offline: false,
+    });
+}
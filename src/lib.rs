@@ -0,0 +1,39 @@
+/*
+    gdman as a library, for tools that want to find, install and switch between
+    versions of Godot without shelling out to the gdman command. The command
+    line application is a thin layer on top of this.
+
+    Call config::init and github::releases_cache::init before anything else,
+    otherwise the config file is ignored and the releases cache uses its defaults.
+    Progress of downloads and extractions is reported to the ProgressReporter
+    passed in, see the progress module.
+
+    Hold lock::lock() while calling anything which changes the installed versions,
+    export templates or godot link (e.g. install_release, install_templates and
+    set_active_godot_version), as the commands do. The functions don't take it
+    themselves, so that several can be called under the one lock, but without it
+    they can trip over other gdman processes doing the same.
+*/
+
+#![allow(clippy::needless_return)]
+
+pub mod common;
+pub mod config;
+pub mod custom;
+pub mod error;
+pub mod gd_semver;
+pub mod gdman;
+pub mod github;
+pub mod lock;
+pub mod mirrors;
+pub mod progress;
+pub mod project;
+pub mod release_source;
+
+pub use common::{Architecture, Channel, Flavour, FromOS, Platform};
+pub use error::GdmanError;
+pub use gd_semver::MaybeVersionOrVersionReq;
+// The version manager's functions are used the most, so they're available from the crate root
+pub use gdman::*;
+pub use github::{github_repo::Release, godot_repo::GodotVersionNameParts};
+pub use progress::{NoProgress, ProgressEvent, ProgressReporter};
@@ -0,0 +1,109 @@
+/*
+    Advisory lock on the gdman home directory, taken by anything which changes
+    the installed versions or the godot link, so that concurrent gdman processes
+    (e.g. parallel CI jobs) don't trip over each other.
+*/
+
+use std::{
+    fs::{self, File, TryLockError},
+    io::{Seek, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{self, ConfigKey},
+    error::GdmanError,
+    gdman,
+};
+
+const LOCK_FILE_NAME: &str = "gdman.lock";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10 * 60;
+const RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Holds the lock until dropped
+pub struct HomeLock {
+    _file: File,
+}
+
+/// Takes the lock, waiting for any other gdman process holding it to finish.
+/// Gives up after the number of seconds in the lock-timeout setting.
+pub async fn lock() -> Result<HomeLock, GdmanError> {
+    let home_dir = gdman::get_home_dir()?;
+    if let Err(e) = fs::create_dir_all(&home_dir) {
+        return Err(GdmanError::io(
+            format!("Failed to create home directory {}", home_dir.display()),
+            e,
+        ));
+    }
+
+    let path = home_dir.join(LOCK_FILE_NAME);
+
+    let mut file = match File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+    {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to open lock file {}", path.display()),
+                e,
+            ))
+        }
+        Ok(f) => f,
+    };
+
+    let timeout = Duration::from_secs(
+        config::resolve(ConfigKey::LockTimeout, None, None)?.unwrap_or(DEFAULT_TIMEOUT_SECONDS),
+    );
+    let started = Instant::now();
+    let mut waiting = false;
+
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::Error(e)) => {
+                return Err(GdmanError::io(
+                    format!("Failed to lock {}", path.display()),
+                    e,
+                ))
+            }
+            Err(TryLockError::WouldBlock) => {
+                let owner = get_owner(&path);
+                if started.elapsed() >= timeout {
+                    return Err(GdmanError::LockTimeout {
+                        seconds: timeout.as_secs(),
+                        owner,
+                    });
+                }
+                if !waiting {
+                    match &owner {
+                        None => log::info!("Waiting for another gdman process"),
+                        Some(pid) => log::info!("Waiting for another gdman process (pid {pid})"),
+                    }
+                    waiting = true;
+                }
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+
+    log::trace!("Locked {}", path.display());
+
+    // Record who holds the lock, so anyone waiting for it can say
+    let _ = file
+        .set_len(0)
+        .and_then(|_| file.rewind())
+        .and_then(|_| write!(file, "{}", std::process::id()));
+
+    return Ok(HomeLock { _file: file });
+}
+
+/// Gets the pid of the process holding the lock, if it's recorded one
+fn get_owner(path: &std::path::Path) -> Option<String> {
+    return match fs::read_to_string(path) {
+        Ok(pid) if !pid.trim().is_empty() => Some(pid.trim().to_owned()),
+        _ => None,
+    };
+}
@@ -1,9 +1,9 @@
+#![allow(clippy::needless_return)]
+
 mod cli;
 mod commands;
-mod common;
-mod gd_semver;
-mod gdman;
-mod github;
+mod output;
+mod progress_bars;
 
 use std::process::ExitCode;
 
@@ -12,34 +12,51 @@ use fern::colors::{Color, ColoredLevelConfig};
 
 use cli::Commands;
 use commands::common::RunCommand;
+use gdman::{config, error::GdmanError, github::releases_cache};
 
 #[tokio::main]
 async fn main() -> ExitCode {
     let args = cli::Args::parse();
 
+    output::init(args.output);
+
     if let Err(init_err) = init_logger(args.verbose) {
         println!("Failed to initialize logger\n{init_err}");
         return ExitCode::FAILURE;
     }
 
+    if let Err(e) = config::init() {
+        log::error!("Failed to load config\n{}", e.report());
+        return ExitCode::from(e.exit_code());
+    }
+
+    releases_cache::init(args.offline);
+
     let res = match args.command {
         Commands::Install(install) => install.run().await,
+        Commands::LinkCustom(link_custom) => link_custom.run().await,
         Commands::Uninstall(uninstall) => uninstall.run().await,
         Commands::Current(current) => current.run().await,
         Commands::List(list) => list.run().await,
         Commands::Update(update) => update.run().await,
+        Commands::Run(run) => run.run().await,
+        Commands::Search(search) => search.run().await,
+        Commands::Templates(templates) => templates.run().await,
+        Commands::Cache(cache) => cache.run().await,
+        Commands::Config(config) => config.run().await,
+        Commands::Doctor(doctor) => doctor.run().await,
     };
 
     return match res {
         Err(e) => {
-            log::error!("Failed!\n{e}");
-            ExitCode::FAILURE
+            log::error!("Failed!\n{}", e.report());
+            ExitCode::from(e.exit_code())
         }
         Ok(_) => ExitCode::SUCCESS,
     };
 }
 
-fn init_logger(verbose: bool) -> Result<(), String> {
+fn init_logger(verbose: bool) -> Result<(), GdmanError> {
     let log_level = match verbose {
         true => log::LevelFilter::Trace,
         _ => log::LevelFilter::Info,
@@ -58,15 +75,20 @@ fn init_logger(verbose: bool) -> Result<(), String> {
                 out.finish(format_args!("{} {}", colors.color(level), message));
             }
         })
-        .level(log_level)
-        .chain(std::io::stdout());
+        .level(log_level);
+
+    // Keep stdout clean for the JSON output
+    builder = match output::is_json() {
+        true => builder.chain(std::io::stderr()),
+        false => builder.chain(std::io::stdout()),
+    };
 
     if !cfg!(debug_assertions) {
         builder = builder.level_for("lnk", log::LevelFilter::Off);
     }
 
     return match builder.apply() {
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(GdmanError::Other(e.to_string())),
         Ok(_) => Ok(()),
     };
 }
@@ -0,0 +1,119 @@
+/*
+    Mirrors that Godot's archives can be downloaded from instead of the release source,
+    for when it's slow or blocked. The mirrors set via the mirrors setting are tried
+    in order, falling back to the release source if none of them work. Archives
+    are still verified against the checksums published by the release source.
+*/
+
+use std::{fmt, str::FromStr};
+
+use reqwest::Url;
+
+use crate::{
+    config::{self, ConfigKey},
+    error::GdmanError,
+};
+
+const GODOT_CDN_URL: &str = "https://downloads.godotengine.org/";
+const TUXFAMILY_URL: &str = "https://downloads.tuxfamily.org/godotengine/";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mirror {
+    /// The official download CDN used by the Godot website
+    GodotCdn,
+    /// The archive of every release hosted by TuxFamily
+    TuxFamily,
+    /// Any other mirror using the same layout as the TuxFamily archive,
+    /// e.g. 4.3/Godot_v4.3-stable_linux.x86_64.zip or 4.4/beta1/mono/Godot_v4.4-beta1_mono_win64.zip
+    Archive(Url),
+}
+
+impl Mirror {
+    /// Gets the URL to download the asset of the release with the specified tag from
+    pub fn get_asset_url(&self, tag_name: &str, asset_name: &str) -> String {
+        let (version, flavor) = tag_name.split_once('-').unwrap_or((tag_name, "stable"));
+
+        return match self {
+            // The CDN identifies the asset by the part of its name after the version
+            Mirror::GodotCdn => {
+                let slug = asset_name
+                    .strip_prefix(&format!("Godot_v{tag_name}_"))
+                    .unwrap_or(asset_name);
+                format!("{GODOT_CDN_URL}?version={version}&flavor={flavor}&slug={slug}")
+            }
+            Mirror::TuxFamily => get_archive_url(TUXFAMILY_URL, version, flavor, asset_name),
+            Mirror::Archive(url) => get_archive_url(url.as_str(), version, flavor, asset_name),
+        };
+    }
+}
+
+/// Pre-releases are in a directory named after the flavor within the version's directory,
+/// and the mono builds are in a further mono directory
+fn get_archive_url(base_url: &str, version: &str, flavor: &str, asset_name: &str) -> String {
+    let mut url = base_url.trim_end_matches('/').to_owned();
+    url.push('/');
+    url.push_str(version);
+    if flavor != "stable" {
+        url.push('/');
+        url.push_str(flavor);
+    }
+    if asset_name.contains("_mono_") {
+        url.push_str("/mono");
+    }
+    url.push('/');
+    url.push_str(asset_name);
+    return url;
+}
+
+impl FromStr for Mirror {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "godot-cdn" => Ok(Mirror::GodotCdn),
+            "tuxfamily" => Ok(Mirror::TuxFamily),
+            _ => match Url::parse(s) {
+                Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                    Ok(Mirror::Archive(url))
+                }
+                _ => Err(format!(
+                    "Invalid mirror {s}, expected godot-cdn, tuxfamily or an http(s) URL"
+                )),
+            },
+        };
+    }
+}
+
+impl fmt::Display for Mirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Mirror::GodotCdn => write!(f, "godot-cdn"),
+            Mirror::TuxFamily => write!(f, "tuxfamily"),
+            Mirror::Archive(url) => write!(f, "{url}"),
+        };
+    }
+}
+
+/// A comma-separated list of mirrors, as the mirrors setting is stored
+pub struct Mirrors(pub Vec<Mirror>);
+
+impl FromStr for Mirrors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Ok(Mirrors(
+            s.split(',')
+                .map(|m| m.trim())
+                .filter(|m| !m.is_empty())
+                .map(Mirror::from_str)
+                .collect::<Result<Vec<Mirror>, String>>()?,
+        ));
+    }
+}
+
+/// Gets the mirrors to try, in order, from the mirrors setting
+pub fn from_config() -> Result<Vec<Mirror>, GdmanError> {
+    return Ok(config::resolve::<Mirrors>(ConfigKey::Mirrors, None, None)?
+        .map(|m| m.0)
+        .unwrap_or_default());
+}
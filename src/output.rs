@@ -0,0 +1,99 @@
+/*
+    Machine-readable output of command results. When JSON output is
+    selected, logs are written to stderr so that stdout only contains
+    the JSON document.
+*/
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use gdman::{error::GdmanError, GodotVersionInfo};
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+pub fn init(format: OutputFormat) {
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    return OUTPUT_FORMAT.get() == Some(&OutputFormat::Json);
+}
+
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), GdmanError> {
+    return match serde_json::to_string_pretty(value) {
+        Err(e) => Err(GdmanError::Other(format!("Error serializing output\n{e}"))),
+        Ok(json) => {
+            writeln!(io::stdout(), "{json}").map_err(|e| GdmanError::io("Error writing output", e))
+        }
+    };
+}
+
+/// An installed version of Godot
+#[derive(Serialize)]
+pub struct VersionOutput {
+    pub name: String,
+    pub version: String,
+    pub version_string: String,
+    pub flavour: String,
+    pub platform: String,
+    pub architecture: String,
+    pub path: PathBuf,
+    pub active: bool,
+    pub installed_at: Option<DateTime<Utc>>,
+    pub size: u64,
+    pub custom: bool,
+}
+
+impl VersionOutput {
+    pub fn from_installed(version: &GodotVersionInfo, active: bool) -> VersionOutput {
+        let installed_at = fs::metadata(&version.path)
+            .and_then(|m| m.created().or(m.modified()))
+            .ok()
+            .map(DateTime::<Utc>::from);
+
+        return VersionOutput {
+            name: version.name_parts.version_name.clone(),
+            version: version.name_parts.version.to_string(),
+            version_string: version.name_parts.version_string.clone(),
+            flavour: version.name_parts.flavour.to_string(),
+            platform: version.name_parts.platform.to_string().to_lowercase(),
+            architecture: version.name_parts.architecture.to_string(),
+            path: version.path.clone(),
+            active,
+            installed_at,
+            size: match version.custom {
+                true => fs::metadata(&version.path).map(|m| m.len()).unwrap_or(0),
+                false => gdman::get_dir_size(&version.path),
+            },
+            custom: version.custom,
+        };
+    }
+}
+
+/// A version of Godot available to install
+#[derive(Serialize)]
+pub struct RemoteVersionOutput {
+    pub name: String,
+    pub version: String,
+    pub version_string: String,
+    pub flavour: String,
+    pub platform: String,
+    pub architecture: String,
+    pub published_at: DateTime<Utc>,
+    pub size: i64,
+    pub installed: bool,
+    pub active: bool,
+}
@@ -0,0 +1,44 @@
+/*
+    Progress reporting for the long-running parts of installing a version,
+    i.e. downloading and extracting archives. gdman doesn't display anything
+    itself, it reports events to whatever the caller passes in, e.g. the CLI
+    draws progress bars, while other tools can ignore them with NoProgress.
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgressEvent {
+    /// A download has started. The total is the size in bytes (0 if unknown), and the
+    /// position is the number of bytes already downloaded if the download is being resumed.
+    DownloadStarted { total: u64, position: u64 },
+    /// The specified number of bytes have been downloaded since the last event
+    Downloaded(u64),
+    /// The download has completed
+    DownloadFinished,
+    /// The download failed, it may be started again if it's being retried
+    DownloadFailed,
+    /// Extracting an archive containing the specified number of entries has started
+    ExtractStarted { entries: u64 },
+    /// The specified number of entries have been extracted since the last event
+    Extracted(u64),
+    /// All of the entries have been extracted
+    ExtractFinished,
+}
+
+/// Receives the progress of downloads and extractions. Any closure taking
+/// a ProgressEvent can be used, so callers don't need their own type.
+pub trait ProgressReporter: Send + Sync {
+    fn report(&self, event: ProgressEvent);
+}
+
+impl<F: Fn(ProgressEvent) + Send + Sync> ProgressReporter for F {
+    fn report(&self, event: ProgressEvent) {
+        self(event);
+    }
+}
+
+/// Ignores all progress
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn report(&self, _event: ProgressEvent) {}
+}
@@ -0,0 +1,97 @@
+/*
+    Draws the progress reported by gdman as progress bars in the terminal.
+*/
+
+use std::sync::Mutex;
+
+use gdman::progress::{ProgressEvent, ProgressReporter};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+const DOWNLOAD_TEMPLATE: &str = "[{bar:40.cyan/blue}] {bytes}/{total_bytes} - {msg}";
+const EXTRACT_TEMPLATE: &str = "[{bar:40.cyan/blue}] {pos}/{len} - {msg}";
+
+/// Shows one progress bar at a time, for whichever download or extraction is in progress
+#[derive(Default)]
+pub struct ProgressBars {
+    bar: Mutex<Option<ProgressBar>>,
+    multi: MultiProgress,
+}
+
+impl ProgressBars {
+    /// Progress bars for another download running at the same time,
+    /// which are drawn below these rather than over them
+    pub fn alongside(&self) -> ProgressBars {
+        return ProgressBars {
+            bar: Mutex::new(None),
+            multi: self.multi.clone(),
+        };
+    }
+
+    fn start(&self, length: u64, position: u64, template: &str, message: &'static str) {
+        let style = match ProgressStyle::default_bar().template(template) {
+            Err(e) => {
+                log::trace!("Invalid progress bar template {template}\n{e}");
+                ProgressStyle::default_bar()
+            }
+            Ok(s) => s,
+        };
+
+        let bar = self.multi.add(ProgressBar::new(length));
+        bar.set_style(style.progress_chars("#>-"));
+        bar.set_message(message);
+        bar.set_position(position);
+
+        if let Some(previous) = self.replace(Some(bar)) {
+            previous.abandon();
+        }
+    }
+
+    fn inc(&self, delta: u64) {
+        if let Some(bar) = self.lock().as_ref() {
+            bar.inc(delta);
+        }
+    }
+
+    fn replace(&self, bar: Option<ProgressBar>) -> Option<ProgressBar> {
+        return std::mem::replace(&mut *self.lock(), bar);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ProgressBar>> {
+        // A panic while drawing a progress bar doesn't leave anything inconsistent
+        return self.bar.lock().unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+impl ProgressReporter for ProgressBars {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::DownloadStarted { total, position } => {
+                self.start(total, position, DOWNLOAD_TEMPLATE, "Download progress")
+            }
+            ProgressEvent::ExtractStarted { entries } => {
+                self.start(entries, 0, EXTRACT_TEMPLATE, "Extracting contents from zip")
+            }
+            ProgressEvent::Downloaded(bytes) => self.inc(bytes),
+            ProgressEvent::Extracted(entries) => self.inc(entries),
+            ProgressEvent::DownloadFinished | ProgressEvent::ExtractFinished => {
+                if let Some(bar) = self.replace(None) {
+                    bar.finish();
+                }
+            }
+            ProgressEvent::DownloadFailed => {
+                if let Some(bar) = self.replace(None) {
+                    bar.abandon();
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ProgressBars {
+    fn drop(&mut self) {
+        // Leave the bar of a failed extraction where it got to
+        if let Some(bar) = self.replace(None) {
+            bar.abandon();
+        }
+    }
+}
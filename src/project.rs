@@ -0,0 +1,160 @@
+/*
+    Discovery of the Godot version a project targets, declared via
+    either a .godot-version or gdman.toml file in the project directory
+    (or any of its parents).
+*/
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    common::{Architecture, Flavour},
+    error::GdmanError,
+    gd_semver::MaybeVersionOrVersionReq,
+};
+
+const VERSION_FILE_NAME: &str = ".godot-version";
+const TOML_FILE_NAME: &str = "gdman.toml";
+
+#[derive(Clone, Debug)]
+pub struct ProjectVersion {
+    pub path: PathBuf,
+    pub version: MaybeVersionOrVersionReq,
+    pub flavour: Option<Flavour>,
+    pub architecture: Option<Architecture>,
+}
+
+#[derive(Deserialize)]
+struct ProjectToml {
+    version: String,
+    flavour: Option<String>,
+    architecture: Option<String>,
+}
+
+/// Walks up from the current directory looking for a project version file.
+/// Returns None if no such file exists in the current directory or any of its parents.
+pub fn find_project_version() -> Result<Option<ProjectVersion>, GdmanError> {
+    let current_dir = match env::current_dir() {
+        Err(e) => return Err(GdmanError::io("Unable to determine current directory", e)),
+        Ok(d) => d,
+    };
+
+    for dir in current_dir.ancestors() {
+        let version_file_path = dir.join(VERSION_FILE_NAME);
+        if version_file_path.is_file() {
+            log::trace!("Found project version file {}", version_file_path.display());
+            return parse_version_file(&version_file_path).map(Some);
+        }
+
+        let toml_file_path = dir.join(TOML_FILE_NAME);
+        if toml_file_path.is_file() {
+            log::trace!("Found project version file {}", toml_file_path.display());
+            return parse_toml_file(&toml_file_path).map(Some);
+        }
+    }
+
+    log::trace!("No project version file found");
+    return Ok(None);
+}
+
+/// The .godot-version file contains the version, optionally followed by the flavour
+/// and/or architecture, e.g. `4.2.1`, `~4.3 mono` or `>=4.1, <4.3 mono x64`.
+/// Lines starting with `#` are treated as comments.
+fn parse_version_file(path: &Path) -> Result<ProjectVersion, GdmanError> {
+    let content = read_file(path)?;
+
+    let line = match content
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+    {
+        None => {
+            return Err(GdmanError::InvalidInput(format!(
+                "No version specified in {}",
+                path.display()
+            )))
+        }
+        Some(l) => l,
+    };
+
+    let mut tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut flavour: Option<Flavour> = None;
+    let mut architecture: Option<Architecture> = None;
+
+    // The flavour and architecture are optional trailing tokens, anything
+    // before them is the version (which may itself contain whitespace)
+    while let Some(token) = tokens.last() {
+        if let (None, Ok(f)) = (&flavour, Flavour::from_str(token)) {
+            flavour = Some(f);
+        } else if let (None, Ok(a)) = (&architecture, Architecture::from_str(token)) {
+            architecture = Some(a);
+        } else {
+            break;
+        }
+        tokens.pop();
+    }
+
+    return Ok(ProjectVersion {
+        path: path.to_path_buf(),
+        version: parse_version(&tokens.join(" "), path)?,
+        flavour,
+        architecture,
+    });
+}
+
+fn parse_toml_file(path: &Path) -> Result<ProjectVersion, GdmanError> {
+    let content = read_file(path)?;
+
+    let project: ProjectToml = match toml::from_str(&content) {
+        Err(e) => return Err(GdmanError::parse(format!("Invalid {}", path.display()), e)),
+        Ok(p) => p,
+    };
+
+    let flavour = match project.flavour {
+        None => None,
+        Some(f) => match Flavour::from_str(&f) {
+            Err(_) => {
+                return Err(GdmanError::InvalidInput(format!(
+                    "Invalid flavour {f} in {}",
+                    path.display()
+                )))
+            }
+            Ok(f) => Some(f),
+        },
+    };
+
+    let architecture = match project.architecture {
+        None => None,
+        Some(a) => match Architecture::from_str(&a) {
+            Err(_) => {
+                return Err(GdmanError::InvalidInput(format!(
+                    "Invalid architecture {a} in {}",
+                    path.display()
+                )))
+            }
+            Ok(a) => Some(a),
+        },
+    };
+
+    return Ok(ProjectVersion {
+        path: path.to_path_buf(),
+        version: parse_version(&project.version, path)?,
+        flavour,
+        architecture,
+    });
+}
+
+fn parse_version(version: &str, path: &Path) -> Result<MaybeVersionOrVersionReq, GdmanError> {
+    return MaybeVersionOrVersionReq::from_str(version)
+        .map_err(|e| GdmanError::InvalidInput(format!("{e} in {}", path.display())));
+}
+
+fn read_file(path: &Path) -> Result<String, GdmanError> {
+    return fs::read_to_string(path)
+        .map_err(|e| GdmanError::io(format!("Error reading {}", path.display()), e));
+}
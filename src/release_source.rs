@@ -0,0 +1,163 @@
+/*
+    Where releases of Godot are found and downloaded from. Releases come from GitHub
+    by default, but can instead come from a plain HTTP directory index (such as an
+    internal mirror) or a directory on disk, which is mostly useful for testing.
+    Both of those are laid out like <repo>/<tag>/<asset>, e.g.
+    godot/4.3-stable/Godot_v4.3-stable_linux.x86_64.zip, the repos being the
+    GitHub repos the releases would otherwise come from.
+*/
+
+pub mod github;
+pub mod http_index;
+pub mod local;
+
+use std::{future::Future, path::Path};
+
+use strum_macros::{Display, EnumString, VariantNames};
+
+use crate::{
+    common::Channel,
+    config::{self, ConfigKey},
+    error::GdmanError,
+    github::github_repo::{self as gh, Asset, Release},
+    progress::ProgressReporter,
+};
+
+use self::{github::GitHubSource, http_index::HttpIndexSource, local::LocalSource};
+
+/// The kinds of release source that can be selected via the release-source setting
+#[derive(EnumString, VariantNames, Display, Clone, Copy, Debug, PartialEq)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SourceKind {
+    Github,
+    HttpIndex,
+    Local,
+}
+
+pub trait ReleaseSource: Sync {
+    /// Lists every release in the repo
+    fn get_releases(
+        &self,
+        repo: &str,
+        client: &reqwest::Client,
+    ) -> impl Future<Output = Result<Vec<Release>, GdmanError>> + Send;
+
+    /// Finds the release satisfying the specified version, as described by github_repo::select_release.
+    /// Sources which can find a release without listing every release should override this.
+    fn find_release(
+        &self,
+        repo: &str,
+        version_exact: &Option<semver::Version>,
+        version_like: &Option<semver::VersionReq>,
+        channel: &Channel,
+        client: &reqwest::Client,
+    ) -> impl Future<Output = Result<Release, GdmanError>> + Send {
+        async move {
+            let releases = self.get_releases(repo, client).await?;
+            return match gh::select_release(&releases, version_exact, version_like, channel) {
+                None => Err(GdmanError::VersionNotFound(
+                    "No matching release found".to_owned(),
+                )),
+                Some(release) => {
+                    log::info!("Found release with version {}", release.tag_name);
+                    Ok(release)
+                }
+            };
+        }
+    }
+
+    /// Downloads the asset of a release found by this source to the specified file
+    fn fetch_asset(
+        &self,
+        asset: &Asset,
+        out_file_path: &Path,
+        client: &reqwest::Client,
+        progress: &dyn ProgressReporter,
+    ) -> impl Future<Output = Result<(), GdmanError>> + Send;
+}
+
+/// The release source selected by the release-source setting
+pub enum Source {
+    GitHub(GitHubSource),
+    HttpIndex(HttpIndexSource),
+    Local(LocalSource),
+}
+
+/// Gets the release source selected by the release-source and release-source-url settings
+pub fn from_config() -> Result<Source, GdmanError> {
+    let kind = config::resolve::<SourceKind>(ConfigKey::ReleaseSource, None, None)?
+        .unwrap_or(SourceKind::Github);
+
+    return match kind {
+        SourceKind::Github => Ok(Source::GitHub(GitHubSource)),
+        SourceKind::HttpIndex => Ok(Source::HttpIndex(HttpIndexSource::new(&get_url(kind)?)?)),
+        SourceKind::Local => Ok(Source::Local(LocalSource::new(&get_url(kind)?))),
+    };
+}
+
+/// Gets the URL (or path) of the release source, which every source other than GitHub requires
+fn get_url(kind: SourceKind) -> Result<String, GdmanError> {
+    return match config::resolve::<String>(ConfigKey::ReleaseSourceUrl, None, None)? {
+        None => Err(GdmanError::InvalidInput(format!(
+            "The {} setting is required when the {} setting is {kind}",
+            ConfigKey::ReleaseSourceUrl,
+            ConfigKey::ReleaseSource
+        ))),
+        Some(url) => {
+            log::trace!("Using {kind} release source {url}");
+            Ok(url)
+        }
+    };
+}
+
+impl ReleaseSource for Source {
+    async fn get_releases(
+        &self,
+        repo: &str,
+        client: &reqwest::Client,
+    ) -> Result<Vec<Release>, GdmanError> {
+        return match self {
+            Source::GitHub(s) => s.get_releases(repo, client).await,
+            Source::HttpIndex(s) => s.get_releases(repo, client).await,
+            Source::Local(s) => s.get_releases(repo, client).await,
+        };
+    }
+
+    async fn find_release(
+        &self,
+        repo: &str,
+        version_exact: &Option<semver::Version>,
+        version_like: &Option<semver::VersionReq>,
+        channel: &Channel,
+        client: &reqwest::Client,
+    ) -> Result<Release, GdmanError> {
+        return match self {
+            Source::GitHub(s) => {
+                s.find_release(repo, version_exact, version_like, channel, client)
+                    .await
+            }
+            Source::HttpIndex(s) => {
+                s.find_release(repo, version_exact, version_like, channel, client)
+                    .await
+            }
+            Source::Local(s) => {
+                s.find_release(repo, version_exact, version_like, channel, client)
+                    .await
+            }
+        };
+    }
+
+    async fn fetch_asset(
+        &self,
+        asset: &Asset,
+        out_file_path: &Path,
+        client: &reqwest::Client,
+        progress: &dyn ProgressReporter,
+    ) -> Result<(), GdmanError> {
+        return match self {
+            Source::GitHub(s) => s.fetch_asset(asset, out_file_path, client, progress).await,
+            Source::HttpIndex(s) => s.fetch_asset(asset, out_file_path, client, progress).await,
+            Source::Local(s) => s.fetch_asset(asset, out_file_path, client, progress).await,
+        };
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use crate::{
+    common::Channel,
+    error::GdmanError,
+    gdman,
+    github::github_repo::{self as gh, Asset, Release},
+    progress::ProgressReporter,
+};
+
+use super::ReleaseSource;
+
+const OWNER: &str = "godotengine";
+
+/// Releases published to the godotengine repos on GitHub, cached as described in releases_cache
+pub struct GitHubSource;
+
+impl ReleaseSource for GitHubSource {
+    async fn get_releases(
+        &self,
+        repo: &str,
+        client: &reqwest::Client,
+    ) -> Result<Vec<Release>, GdmanError> {
+        return gh::get_releases(OWNER, repo, client).await;
+    }
+
+    /// For an exact version, walks through the pages of releases until one contains it,
+    /// rather than fetching every release. Otherwise every page is fetched, since the
+    /// latest version satisfying a requirement could be on any of them.
+    async fn find_release(
+        &self,
+        repo: &str,
+        version_exact: &Option<semver::Version>,
+        version_like: &Option<semver::VersionReq>,
+        channel: &Channel,
+        client: &reqwest::Client,
+    ) -> Result<Release, GdmanError> {
+        return gh::find_release(OWNER, repo, version_exact, version_like, channel, client).await;
+    }
+
+    async fn fetch_asset(
+        &self,
+        asset: &Asset,
+        out_file_path: &Path,
+        client: &reqwest::Client,
+        progress: &dyn ProgressReporter,
+    ) -> Result<(), GdmanError> {
+        return gdman::download_file(client, &asset.browser_download_url, out_file_path, progress)
+            .await;
+    }
+}
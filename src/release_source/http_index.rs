@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::{header, StatusCode, Url};
+
+use crate::{
+    common::Channel,
+    error::GdmanError,
+    gdman,
+    github::{
+        github_repo::{self as gh, Asset, Release},
+        releases_cache,
+    },
+    progress::ProgressReporter,
+};
+
+use super::ReleaseSource;
+
+/// Releases served as a plain HTTP directory index, like the listings generated by most web servers,
+/// where each release is a directory named after its tag containing the release's assets.
+/// The listings don't say when releases were published or how big their assets are, so the
+/// publish date is taken from the Last-Modified header of the release's listing if there is one.
+pub struct HttpIndexSource {
+    base_url: Url,
+}
+
+/// The links in a directory listing, split into directories and files
+struct Listing {
+    dirs: Vec<(String, Url)>,
+    files: Vec<(String, Url)>,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl HttpIndexSource {
+    pub fn new(url: &str) -> Result<HttpIndexSource, GdmanError> {
+        return Ok(HttpIndexSource {
+            base_url: parse_dir_url(url)?,
+        });
+    }
+
+    /// Lists the releases in the repo without their assets, since that takes a request per release
+    async fn get_release_tags(
+        &self,
+        repo: &str,
+        client: &reqwest::Client,
+    ) -> Result<Vec<Release>, GdmanError> {
+        let repo_url = join_dir(&self.base_url, repo)?;
+
+        let listing = match get_listing(&repo_url, client).await? {
+            None => {
+                log::trace!("{repo_url} not found, assuming there are no releases");
+                return Ok(Vec::new());
+            }
+            Some(l) => l,
+        };
+
+        return Ok(listing
+            .dirs
+            .into_iter()
+            .map(|(tag_name, url)| Release {
+                url: url.to_string(),
+                tag_name,
+                published_at: DateTime::UNIX_EPOCH,
+                assets: Vec::new(),
+            })
+            .collect());
+    }
+
+    /// Fills in the assets and publish date of a release listed by get_release_tags
+    async fn get_release_assets(
+        &self,
+        mut release: Release,
+        client: &reqwest::Client,
+    ) -> Result<Release, GdmanError> {
+        let url = parse_dir_url(&release.url)?;
+
+        let listing = match get_listing(&url, client).await? {
+            None => {
+                return Err(GdmanError::Http {
+                    url: url.to_string(),
+                    status: StatusCode::NOT_FOUND,
+                    message: None,
+                })
+            }
+            Some(l) => l,
+        };
+
+        release.published_at = listing.last_modified.unwrap_or(DateTime::UNIX_EPOCH);
+        release.assets = listing
+            .files
+            .into_iter()
+            .map(|(name, url)| Asset {
+                url: url.to_string(),
+                name,
+                size: 0,
+                browser_download_url: url.to_string(),
+            })
+            .collect();
+
+        return Ok(release);
+    }
+}
+
+impl ReleaseSource for HttpIndexSource {
+    async fn get_releases(
+        &self,
+        repo: &str,
+        client: &reqwest::Client,
+    ) -> Result<Vec<Release>, GdmanError> {
+        let mut releases = Vec::new();
+        for release in self.get_release_tags(repo, client).await? {
+            releases.push(self.get_release_assets(release, client).await?);
+        }
+        return Ok(releases);
+    }
+
+    /// Only the assets of the matching release are listed, rather than those of every release
+    async fn find_release(
+        &self,
+        repo: &str,
+        version_exact: &Option<semver::Version>,
+        version_like: &Option<semver::VersionReq>,
+        channel: &Channel,
+        client: &reqwest::Client,
+    ) -> Result<Release, GdmanError> {
+        let releases = self.get_release_tags(repo, client).await?;
+
+        return match gh::select_release(&releases, version_exact, version_like, channel) {
+            None => Err(GdmanError::VersionNotFound(format!(
+                "No matching release found in {}",
+                self.base_url
+            ))),
+            Some(release) => {
+                log::info!("Found release with version {}", release.tag_name);
+                self.get_release_assets(release, client).await
+            }
+        };
+    }
+
+    async fn fetch_asset(
+        &self,
+        asset: &Asset,
+        out_file_path: &Path,
+        client: &reqwest::Client,
+        progress: &dyn ProgressReporter,
+    ) -> Result<(), GdmanError> {
+        return gdman::download_file(client, &asset.browser_download_url, out_file_path, progress)
+            .await;
+    }
+}
+
+/// Parses the URL of a directory, making sure it ends with a slash
+/// so that joining paths to it doesn't replace the last segment
+fn parse_dir_url(url: &str) -> Result<Url, GdmanError> {
+    let url = match url.ends_with('/') {
+        true => url.to_owned(),
+        false => format!("{url}/"),
+    };
+    return Url::parse(&url)
+        .map_err(|e| GdmanError::InvalidInput(format!("Invalid release source URL {url}\n{e}")));
+}
+
+fn join_dir(url: &Url, dir: &str) -> Result<Url, GdmanError> {
+    return url.join(&format!("{dir}/")).map_err(|e| {
+        GdmanError::InvalidInput(format!("Invalid release source URL {url}{dir}\n{e}"))
+    });
+}
+
+/// Fetches and parses the directory listing at the URL, returning None if there's no such directory
+async fn get_listing(url: &Url, client: &reqwest::Client) -> Result<Option<Listing>, GdmanError> {
+    if releases_cache::is_offline() {
+        return Err(GdmanError::Offline(format!(
+            "Unable to fetch releases from {url} while offline"
+        )));
+    }
+
+    log::trace!("Fetching directory listing {url}");
+
+    let network_error = |e| GdmanError::Network {
+        url: url.to_string(),
+        source: e,
+    };
+
+    let response = match client.get(url.as_str()).send().await {
+        Err(e) => return Err(network_error(e)),
+        Ok(r) => r,
+    };
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(GdmanError::Http {
+            url: url.to_string(),
+            status: response.status(),
+            message: None,
+        });
+    }
+
+    let last_modified = response
+        .headers()
+        .get(header::LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .map(|v| v.with_timezone(&Utc));
+
+    let html = match response.text().await {
+        Err(e) => return Err(network_error(e)),
+        Ok(t) => t,
+    };
+
+    let mut listing = Listing {
+        dirs: Vec::new(),
+        files: Vec::new(),
+        last_modified,
+    };
+
+    let href = Regex::new(r#"(?i)href\s*=\s*["']([^"'?#]+)["']"#).unwrap();
+    for link in href.captures_iter(&html) {
+        // Only links to the directory's children are wanted, not parent
+        // directories, sorting links, or links to other pages
+        let child_url = match url.join(&link[1]) {
+            Err(_) => continue,
+            Ok(u) => u,
+        };
+        let name = match child_url.as_str().strip_prefix(url.as_str()) {
+            None | Some("") | Some("/") => continue,
+            Some(n) => n,
+        };
+        let (entries, name) = match name.strip_suffix('/') {
+            Some(dir) => (&mut listing.dirs, dir.to_owned()),
+            None => (&mut listing.files, name.to_owned()),
+        };
+        // Some listings link to each entry more than once, e.g. from an icon as well as the name
+        if !name.contains('/') && !entries.iter().any(|(n, _)| *n == name) {
+            entries.push((name, child_url));
+        }
+    }
+
+    return Ok(Some(listing));
+}
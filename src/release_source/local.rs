@@ -0,0 +1,170 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+
+use crate::{
+    error::GdmanError,
+    github::github_repo::{Asset, Release},
+    progress::{ProgressEvent, ProgressReporter},
+};
+
+use super::ReleaseSource;
+
+/// Releases in a directory on disk, where each release is a directory named after its tag
+/// containing the release's assets. The releases are published when their directory was last modified.
+pub struct LocalSource {
+    dir: PathBuf,
+}
+
+impl LocalSource {
+    pub fn new(dir: &str) -> LocalSource {
+        return LocalSource {
+            dir: PathBuf::from(dir),
+        };
+    }
+}
+
+impl ReleaseSource for LocalSource {
+    async fn get_releases(
+        &self,
+        repo: &str,
+        _client: &reqwest::Client,
+    ) -> Result<Vec<Release>, GdmanError> {
+        let repo_dir = self.dir.join(repo);
+
+        log::trace!("Reading releases from {}", repo_dir.display());
+
+        let mut releases = Vec::new();
+        for release_dir in read_dir(&repo_dir)?.into_iter().filter(|p| p.is_dir()) {
+            let assets = read_dir(&release_dir)?
+                .into_iter()
+                .filter(|p| p.is_file())
+                .map(|p| get_asset(&p))
+                .collect::<Result<Vec<Asset>, GdmanError>>()?;
+
+            releases.push(Release {
+                url: get_file_url(&release_dir)?,
+                tag_name: get_file_name(&release_dir),
+                published_at: fs::metadata(&release_dir)
+                    .and_then(|m| m.modified())
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or(DateTime::UNIX_EPOCH),
+                assets,
+            });
+        }
+
+        return Ok(releases);
+    }
+
+    async fn fetch_asset(
+        &self,
+        asset: &Asset,
+        out_file_path: &Path,
+        _client: &reqwest::Client,
+        progress: &dyn ProgressReporter,
+    ) -> Result<(), GdmanError> {
+        let asset_path = match Url::parse(&asset.url)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+        {
+            None => {
+                return Err(GdmanError::Other(format!(
+                    "{} is not the path of a file",
+                    asset.url
+                )))
+            }
+            Some(p) => p,
+        };
+
+        if let Some(parent) = out_file_path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                return Err(GdmanError::io(
+                    format!("Failed to create directory {}", parent.display()),
+                    e,
+                ));
+            }
+        }
+
+        log::trace!(
+            "Copying {} to {}",
+            asset_path.display(),
+            out_file_path.display()
+        );
+
+        progress.report(ProgressEvent::DownloadStarted {
+            total: asset.size as u64,
+            position: 0,
+        });
+
+        return match tokio::fs::copy(&asset_path, out_file_path).await {
+            Err(e) => {
+                progress.report(ProgressEvent::DownloadFailed);
+                Err(GdmanError::io(
+                    format!("Failed to copy {}", asset_path.display()),
+                    e,
+                ))
+            }
+            Ok(size) => {
+                progress.report(ProgressEvent::Downloaded(size));
+                progress.report(ProgressEvent::DownloadFinished);
+                Ok(())
+            }
+        };
+    }
+}
+
+/// Lists the entries in the directory, or nothing if the directory doesn't exist
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, GdmanError> {
+    if !dir.exists() {
+        log::trace!(
+            "{} not found, assuming there are no releases",
+            dir.display()
+        );
+        return Ok(Vec::new());
+    }
+    return match fs::read_dir(dir) {
+        Err(e) => Err(GdmanError::io(
+            format!("Failed to read directory {}", dir.display()),
+            e,
+        )),
+        Ok(entries) => Ok(entries.flatten().map(|e| e.path()).collect()),
+    };
+}
+
+fn get_asset(path: &Path) -> Result<Asset, GdmanError> {
+    let url = get_file_url(path)?;
+    return Ok(Asset {
+        url: url.clone(),
+        name: get_file_name(path),
+        size: fs::metadata(path).map(|m| m.len() as i64).unwrap_or(0),
+        browser_download_url: url,
+    });
+}
+
+fn get_file_name(path: &Path) -> String {
+    return path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+}
+
+/// The assets are identified by file:// URLs, so they look like those of the other sources
+fn get_file_url(path: &Path) -> Result<String, GdmanError> {
+    let path = match path.canonicalize() {
+        Err(e) => {
+            return Err(GdmanError::io(
+                format!("Failed to resolve {}", path.display()),
+                e,
+            ))
+        }
+        Ok(p) => p,
+    };
+    return match Url::from_file_path(&path) {
+        Err(_) => Err(GdmanError::Other(format!(
+            "Failed to convert {} to a URL",
+            path.display()
+        ))),
+        Ok(u) => Ok(u.to_string()),
+    };
+}
@@ -0,0 +1,389 @@
+/*
+    Helpers for running gdman end-to-end against fixture releases, served over HTTP
+    as a directory index or read straight from disk, in an isolated home directory.
+*/
+
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    thread,
+};
+
+use async_zip::{base::write::ZipFileWriter, Compression, ZipEntryBuilder};
+use futures_lite::io::Cursor;
+use sha2::{Digest, Sha512};
+
+pub const STABLE_REPO: &str = "godot";
+pub const CHECKSUMS_FILE_NAME: &str = "SHA512-SUMS.txt";
+
+/// A directory for a test to keep everything in, which is deleted when the test finishes
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestDir {
+    pub fn new(name: &str) -> TestDir {
+        let path = env::temp_dir().join(format!("gdman-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        return TestDir { path };
+    }
+
+    pub fn releases_dir(&self) -> PathBuf {
+        return self.path.join("releases");
+    }
+
+    pub fn home_dir(&self) -> PathBuf {
+        return self.path.join("home");
+    }
+
+    pub fn versions_dir(&self) -> PathBuf {
+        return self.home_dir().join("versions");
+    }
+
+    pub fn link_dir(&self) -> PathBuf {
+        return self.path.join("bin");
+    }
+
+    /// The user data directory, where Godot keeps its export templates
+    pub fn data_dir(&self) -> PathBuf {
+        return self.path.join("data");
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The part of the asset names that identifies the platform and architecture gdman is running on
+pub fn platform_suffix() -> &'static str {
+    if cfg!(windows) {
+        return "win64.exe";
+    }
+    if cfg!(target_os = "macos") {
+        return "macos.universal";
+    }
+    if cfg!(target_arch = "aarch64") {
+        return "linux.arm64";
+    }
+    return "linux.x86_64";
+}
+
+/// The name of the version directory a release is installed to, e.g. Godot_v4.3-stable_linux.x86_64
+pub fn version_name(tag: &str) -> String {
+    return format!("Godot_v{tag}_{}", platform_suffix());
+}
+
+/// Adds a release to the fixture releases, containing an archive with a stand-in for the
+/// Godot executable, and the checksums of the release's assets (if `with_checksums`)
+pub fn add_release(releases_dir: &Path, repo: &str, tag: &str, with_checksums: bool) {
+    let release_dir = releases_dir.join(repo).join(tag);
+    fs::create_dir_all(&release_dir).unwrap();
+
+    let version_name = version_name(tag);
+    let exe_path = match cfg!(target_os = "macos") {
+        true => "Godot.app/Contents/MacOS/Godot".to_owned(),
+        false => version_name.clone(),
+    };
+    let content = format!("Godot {tag}");
+    let archive = create_zip(&[ZipEntry::File(&exe_path, content.as_bytes())]);
+    let archive_name = format!("{version_name}.zip");
+    fs::write(release_dir.join(&archive_name), &archive).unwrap();
+
+    if with_checksums {
+        let checksum = format!("{:x}", Sha512::digest(&archive));
+        fs::write(
+            release_dir.join(CHECKSUMS_FILE_NAME),
+            format!("{checksum}  {archive_name}\n"),
+        )
+        .unwrap();
+    }
+}
+
+/// Adds export templates to a fixture release added by add_release, with a stand-in
+/// for one of the templates, and adds their checksum to the release's checksums
+pub fn add_templates(releases_dir: &Path, repo: &str, tag: &str) {
+    let release_dir = releases_dir.join(repo).join(tag);
+
+    let content = format!("Templates {tag}");
+    let archive = create_zip(&[
+        ZipEntry::File("templates/version.txt", tag.as_bytes()),
+        ZipEntry::File("templates/linux_release.x86_64", content.as_bytes()),
+    ]);
+    let archive_name = format!("Godot_v{tag}_export_templates.tpz");
+    fs::write(release_dir.join(&archive_name), &archive).unwrap();
+
+    let checksums_path = release_dir.join(CHECKSUMS_FILE_NAME);
+    if checksums_path.is_file() {
+        let mut checksums = fs::read_to_string(&checksums_path).unwrap();
+        let checksum = format!("{:x}", Sha512::digest(&archive));
+        checksums.push_str(&format!("{checksum}  {archive_name}\n"));
+        fs::write(&checksums_path, checksums).unwrap();
+    }
+}
+
+/// The directory the export templates for the release are installed to
+pub fn templates_dir(test_dir: &TestDir, tag: &str) -> PathBuf {
+    let godot_dir_name = match cfg!(target_os = "linux") {
+        true => "godot",
+        false => "Godot",
+    };
+    let data_dir = match cfg!(target_os = "macos") {
+        true => test_dir.data_dir().join("Library/Application Support"),
+        false => test_dir.data_dir(),
+    };
+    return data_dir
+        .join(godot_dir_name)
+        .join("export_templates")
+        .join(tag.replacen('-', ".", 1));
+}
+
+/// An entry to add to a zip archive
+#[derive(Clone, Copy)]
+pub enum ZipEntry<'a> {
+    /// An executable file with the name and content
+    File(&'a str, &'a [u8]),
+    /// A symlink with the name and target
+    Symlink(&'a str, &'a str),
+}
+
+/// Creates a zip archive with the entries, which are added as-is so they can be used
+/// to craft archives gdman should refuse to extract
+pub fn create_zip(entries: &[ZipEntry]) -> Vec<u8> {
+    return futures_lite::future::block_on(async {
+        let mut writer = ZipFileWriter::new(Cursor::new(Vec::new()));
+        for entry in entries {
+            let (name, content, mode) = match entry {
+                ZipEntry::File(name, content) => (name, *content, 0o100755),
+                ZipEntry::Symlink(name, target) => (name, target.as_bytes(), 0o120777),
+            };
+            let builder = ZipEntryBuilder::new(name.to_string().into(), Compression::Deflate)
+                .unix_permissions(mode);
+            writer.write_entry_whole(builder, content).await.unwrap();
+        }
+        return writer.close().await.unwrap().into_inner();
+    });
+}
+
+/// Overwrites the uncompressed size each entry declares in the archive's central
+/// directory, without changing the entries themselves
+pub fn set_declared_sizes(archive: &mut [u8], size: u32) {
+    const CENTRAL_DIRECTORY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+    const UNCOMPRESSED_SIZE_OFFSET: usize = 24;
+
+    let header_starts: Vec<usize> = archive
+        .windows(4)
+        .enumerate()
+        .filter(|(_, w)| *w == CENTRAL_DIRECTORY_SIGNATURE)
+        .map(|(i, _)| i)
+        .collect();
+    assert!(!header_starts.is_empty());
+
+    for start in header_starts {
+        let offset = start + UNCOMPRESSED_SIZE_OFFSET;
+        archive[offset..offset + 4].copy_from_slice(&size.to_le_bytes());
+    }
+}
+
+/// Every path within the test directory other than in the versions directory, so tests
+/// can check nothing was written anywhere else
+pub fn paths_outside_versions(test_dir: &TestDir) -> Vec<PathBuf> {
+    fn walk(dir: &Path, skip: &Path, paths: &mut Vec<PathBuf>) {
+        let entries = match fs::read_dir(dir) {
+            Err(_) => return,
+            Ok(e) => e,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path == skip {
+                continue;
+            }
+            paths.push(path.clone());
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                walk(&path, skip, paths);
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(&test_dir.path, &test_dir.versions_dir(), &mut paths);
+    paths.sort();
+    return paths;
+}
+
+/// Serves the directory over HTTP on a random port, with a listing for each directory
+/// like those web servers generate, returning the URL of the directory
+pub fn serve_dir(dir: &Path) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let dir = dir.to_owned();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let dir = dir.clone();
+            thread::spawn(move || handle_request(stream, &dir));
+        }
+    });
+
+    return url;
+}
+
+/// Files are served with an ETag and can be requested in ranges, so downloads can be resumed.
+/// HEAD requests are refused, as some mirrors do, so nothing relies on them.
+fn handle_request(mut stream: TcpStream, dir: &Path) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut range_start: Option<u64> = None;
+    let mut if_range: Option<String> = None;
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(n) if n > 2 => (),
+            _ => break,
+        }
+        let (name, value) = match line.split_once(':') {
+            None => continue,
+            Some((name, value)) => (name.to_lowercase(), value.trim().to_owned()),
+        };
+        match name.as_str() {
+            "range" => {
+                range_start = value
+                    .strip_prefix("bytes=")
+                    .and_then(|r| r.strip_suffix('-'))
+                    .and_then(|r| r.parse().ok())
+            }
+            "if-range" => if_range = Some(value),
+            _ => (),
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let url_path = parts.next().unwrap_or_default();
+    let path = dir.join(url_path.trim_start_matches('/'));
+
+    let mut extra_headers = String::new();
+    let (status, content_type, body) = if method == "HEAD" {
+        ("405 Method Not Allowed", "text/plain", Vec::new())
+    } else if path.is_dir() && url_path.ends_with('/') {
+        ("200 OK", "text/html", list_dir(&path).into_bytes())
+    } else if path.is_file() {
+        let content = fs::read(&path).unwrap();
+        let len = content.len() as u64;
+        let etag = format!("\"{len}\"");
+        extra_headers.push_str(&format!("ETag: {etag}\r\nAccept-Ranges: bytes\r\n"));
+        match range_start {
+            Some(start) if if_range.is_none_or(|i| i == etag) => match start < len {
+                true => {
+                    extra_headers.push_str(&format!(
+                        "Content-Range: bytes {start}-{}/{len}\r\n",
+                        len - 1
+                    ));
+                    (
+                        "206 Partial Content",
+                        "application/octet-stream",
+                        content[start as usize..].to_vec(),
+                    )
+                }
+                false => {
+                    extra_headers.push_str(&format!("Content-Range: bytes */{len}\r\n"));
+                    ("416 Range Not Satisfiable", "text/plain", Vec::new())
+                }
+            },
+            _ => ("200 OK", "application/octet-stream", content),
+        }
+    } else {
+        ("404 Not Found", "text/plain", b"Not found".to_vec())
+    };
+
+    let headers = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n",
+        body.len()
+    );
+    let _ = stream.write_all(headers.as_bytes());
+    let _ = stream.write_all(&body);
+}
+
+fn list_dir(dir: &Path) -> String {
+    let mut html = String::from("<html><body><h1>Index</h1><a href=\"../\">../</a>\n");
+    let mut entries: Vec<_> = fs::read_dir(dir).unwrap().flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let href = match entry.path().is_dir() {
+            true => format!("{name}/"),
+            false => name.clone(),
+        };
+        html.push_str(&format!("<a href=\"{href}\">{href}</a>\n"));
+    }
+    html.push_str("</body></html>");
+    return html;
+}
+
+/// Runs gdman with its home, link directory and config isolated within the test directory,
+/// using the specified release source
+pub fn gdman(test_dir: &TestDir, source: &str, source_url: &str, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_gdman"));
+    for (var, _) in env::vars().filter(|(var, _)| var.starts_with("GDMAN_")) {
+        command.env_remove(var);
+    }
+    let output = command
+        .args(args)
+        .current_dir(&test_dir.path)
+        .env("GDMAN_HOME", test_dir.home_dir())
+        .env("GDMAN_LINK_DIR", test_dir.link_dir())
+        .env("GDMAN_CONFIG", test_dir.path.join("config.toml"))
+        .env("GDMAN_RELEASE_SOURCE", source)
+        .env("GDMAN_RELEASE_SOURCE_URL", source_url)
+        .env("HOME", test_dir.data_dir())
+        .env("XDG_DATA_HOME", test_dir.data_dir())
+        .env("APPDATA", test_dir.data_dir())
+        .output()
+        .unwrap();
+
+    println!(
+        "gdman {}\n{}{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    return output;
+}
+
+/// The name of the currently-active version, if any
+pub fn current_version(test_dir: &TestDir, source: &str, source_url: &str) -> Option<String> {
+    let output = gdman(
+        test_dir,
+        source,
+        source_url,
+        &["current", "--output", "json"],
+    );
+    assert!(output.status.success());
+    let current: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    return current["name"].as_str().map(|n| n.to_owned());
+}
+
+/// The names of the installed versions, in no particular order
+pub fn installed_versions(test_dir: &TestDir) -> Vec<String> {
+    let mut versions: Vec<String> = match fs::read_dir(test_dir.versions_dir()) {
+        Err(_) => return Vec::new(),
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|n| !n.starts_with('.'))
+            .collect(),
+    };
+    versions.sort();
+    return versions;
+}
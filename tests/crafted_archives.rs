@@ -0,0 +1,127 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    create_zip, gdman, installed_versions, paths_outside_versions, set_declared_sizes,
+    version_name, TestDir, ZipEntry,
+};
+
+const SOURCE: &str = "local";
+const TAG: &str = "4.3-stable";
+
+/// Installs the archive, made up of the entries along with a stand-in for the Godot
+/// executable, checking it's refused without anything being written outside the versions dir
+fn assert_refused(test_name: &str, entries: &[ZipEntry], declared_size: Option<u32>) {
+    let test_dir = TestDir::new(test_name);
+    let exe_name = version_name(TAG);
+
+    let mut all_entries = vec![ZipEntry::File(&exe_name, b"Godot")];
+    all_entries.extend_from_slice(entries);
+    let mut archive = create_zip(&all_entries);
+    if let Some(size) = declared_size {
+        set_declared_sizes(&mut archive, size);
+    }
+
+    let archive_path = test_dir.path.join(format!("{exe_name}.zip"));
+    fs::write(&archive_path, archive).unwrap();
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    // gdman locks its home while installing, so that's expected to be there
+    fs::create_dir_all(test_dir.home_dir()).unwrap();
+    fs::write(test_dir.home_dir().join("gdman.lock"), "").unwrap();
+    let paths_before = paths_outside_versions(&test_dir);
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--from-file", &archive_path.to_string_lossy()],
+    );
+
+    assert!(!output.status.success());
+    assert!(installed_versions(&test_dir).is_empty());
+    assert_eq!(paths_outside_versions(&test_dir), paths_before);
+}
+
+#[test]
+fn refuses_parent_dir_entries() {
+    assert_refused(
+        "crafted-parent",
+        &[ZipEntry::File("../../../../../../escaped", b"escaped")],
+        None,
+    );
+}
+
+#[test]
+fn refuses_absolute_entries() {
+    let test_dir = TestDir::new("crafted-absolute-target");
+    let escaped = test_dir.path.join("escaped").to_string_lossy().into_owned();
+    assert_refused(
+        "crafted-absolute",
+        &[ZipEntry::File(&escaped, b"escaped")],
+        None,
+    );
+    assert!(!test_dir.path.join("escaped").exists());
+}
+
+#[test]
+fn refuses_drive_entries() {
+    assert_refused(
+        "crafted-drive",
+        &[ZipEntry::File("C:/escaped", b"escaped")],
+        None,
+    );
+}
+
+#[test]
+fn refuses_drive_components() {
+    assert_refused(
+        "crafted-drive-component",
+        &[ZipEntry::File("a/C:/escaped", b"escaped")],
+        None,
+    );
+    assert_refused(
+        "crafted-drive-target",
+        &[ZipEntry::Symlink("link", "a/C:/escaped")],
+        None,
+    );
+}
+
+#[test]
+fn refuses_symlinks_outside() {
+    assert_refused(
+        "crafted-symlink",
+        &[ZipEntry::Symlink("link", "../../../../../..")],
+        None,
+    );
+}
+
+#[test]
+fn refuses_chained_symlinks() {
+    assert_refused(
+        "crafted-chained",
+        &[
+            ZipEntry::Symlink("a", "."),
+            ZipEntry::Symlink("a/b", ".."),
+            ZipEntry::File("b/escaped", b"escaped"),
+        ],
+        None,
+    );
+    assert_refused(
+        "crafted-chained-target",
+        &[ZipEntry::Symlink("a", "."), ZipEntry::Symlink("b", "a/..")],
+        None,
+    );
+}
+
+#[test]
+fn refuses_oversized_archives() {
+    // Declaring just under 4GiB for each of the three entries adds up to more than the limit
+    assert_refused(
+        "crafted-oversized",
+        &[ZipEntry::File("a", b"a"), ZipEntry::File("b", b"b")],
+        Some(0xFFFF_FFF0),
+    );
+}
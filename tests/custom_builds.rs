@@ -0,0 +1,236 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::{
+    add_release, current_version, gdman, installed_versions, version_name, TestDir, STABLE_REPO,
+};
+
+const SOURCE: &str = "local";
+
+/// Creates a stand-in for a self-compiled Godot executable, returning its path
+fn add_build(test_dir: &TestDir) -> PathBuf {
+    let build_dir = test_dir.path.join("godot").join("bin");
+    fs::create_dir_all(&build_dir).unwrap();
+    let exe_path = build_dir.join("godot.editor.custom");
+    fs::write(&exe_path, "Godot custom").unwrap();
+    return exe_path;
+}
+
+fn listed_versions(test_dir: &TestDir, dir: &str) -> Vec<(String, bool)> {
+    let output = gdman(test_dir, SOURCE, dir, &["list", "--output", "json"]);
+    assert!(output.status.success());
+    let versions: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    return versions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| {
+            (
+                v["name"].as_str().unwrap().to_owned(),
+                v["custom"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+}
+
+#[test]
+fn link_activate_and_uninstall() {
+    let test_dir = TestDir::new("custom-lifecycle");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+    let exe_path = add_build(&test_dir);
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &[
+            "link-custom",
+            "patched",
+            &exe_path.to_string_lossy(),
+            "--version",
+            "4.3",
+        ],
+    );
+    assert!(output.status.success());
+    // Linking a build doesn't change the active version unless asked to
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.3-stable"))
+    );
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--custom", "patched"]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some("patched".to_owned())
+    );
+    assert_eq!(
+        listed_versions(&test_dir, &dir),
+        vec![
+            (version_name("4.3-stable"), false),
+            ("patched".to_owned(), true)
+        ]
+    );
+
+    // Installing the version the build is based on activates the official version, not the build
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.3-stable"))
+    );
+
+    // Installed versions can't be activated as custom builds
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--custom", &version_name("4.3-stable")],
+    );
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["uninstall", "--unused", "--force"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        listed_versions(&test_dir, &dir),
+        vec![(version_name("4.3-stable"), false)]
+    );
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+    assert!(exe_path.is_file());
+}
+
+#[test]
+fn link_directory() {
+    let test_dir = TestDir::new("custom-directory");
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+    let exe_path = add_build(&test_dir);
+    let build_dir = exe_path.parent().unwrap().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &[
+            "link-custom",
+            "patched",
+            &build_dir,
+            "--version",
+            "4.4-beta1",
+            "--flavour",
+            "mono",
+            "--activate",
+        ],
+    );
+    assert!(output.status.success());
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["current", "--output", "json"]);
+    assert!(output.status.success());
+    let current: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(current["name"], "patched");
+    assert_eq!(current["version"], "4.4.0-beta1");
+    assert_eq!(current["flavour"], "mono");
+    assert_eq!(current["custom"], true);
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["update", "--patch", "--uninstall"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(exe_path.is_file());
+}
+
+#[test]
+fn link_with_installed_name() {
+    let test_dir = TestDir::new("custom-name-clash");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+    let exe_path = add_build(&test_dir);
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &[
+            "link-custom",
+            &version_name("4.3-stable"),
+            &exe_path.to_string_lossy(),
+            "--version",
+            "4.3",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.3-stable"))
+    );
+}
+
+#[test]
+fn builds_in_same_directory() {
+    let test_dir = TestDir::new("custom-same-dir");
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+    let exe_path = add_build(&test_dir);
+    let other_exe_path = exe_path.with_file_name("godot.editor.other");
+    fs::write(&other_exe_path, "Godot other").unwrap();
+
+    for (name, path) in [("other", &other_exe_path), ("patched", &exe_path)] {
+        let output = gdman(
+            &test_dir,
+            SOURCE,
+            &dir,
+            &[
+                "link-custom",
+                name,
+                &path.to_string_lossy(),
+                "-v",
+                "4.3",
+                "--activate",
+            ],
+        );
+        assert!(output.status.success());
+    }
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["current", "--output", "json"]);
+    assert!(output.status.success());
+    let current: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(current["name"], "patched");
+    assert_eq!(
+        current["path"].as_str().map(PathBuf::from),
+        Some(exe_path.canonicalize().unwrap())
+    );
+    assert_eq!(current["size"], fs::metadata(&exe_path).unwrap().len());
+
+    // Only the build that isn't active is unused, despite them being in the same directory
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["uninstall", "--unused", "--force"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        listed_versions(&test_dir, &dir),
+        vec![("patched".to_owned(), true)]
+    );
+    assert!(other_exe_path.is_file());
+}
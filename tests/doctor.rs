@@ -0,0 +1,41 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{add_release, gdman, TestDir, STABLE_REPO};
+
+const SOURCE: &str = "local";
+
+#[test]
+fn keeps_partial_downloads() {
+    let test_dir = TestDir::new("doctor-partial");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+
+    let downloads_dir = test_dir.home_dir().join("downloads");
+    fs::create_dir_all(&downloads_dir).unwrap();
+    let part_path = downloads_dir.join("Godot_v4.4-stable_linux.x86_64.zip.part");
+    let etag_path = downloads_dir.join("Godot_v4.4-stable_linux.x86_64.zip.part.etag");
+    let leftover_path = downloads_dir.join("Godot_v4.2-stable_linux.x86_64.zip");
+    for path in [&part_path, &etag_path, &leftover_path] {
+        fs::write(path, "partial").unwrap();
+    }
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["doctor", "--fix"]);
+    let log = String::from_utf8_lossy(&output.stderr).into_owned()
+        + &String::from_utf8_lossy(&output.stdout);
+    assert!(log.contains(&format!(
+        "Note: {} is a partial download",
+        part_path.display()
+    )));
+    assert!(!log.contains(&format!("Problem: {}", part_path.display())));
+
+    assert!(part_path.is_file());
+    assert!(etag_path.is_file());
+    assert!(!leftover_path.exists());
+}
@@ -0,0 +1,166 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    add_release, current_version, gdman, installed_versions, serve_dir, version_name, TestDir,
+    STABLE_REPO,
+};
+
+const SOURCE: &str = "http-index";
+
+#[test]
+fn install_update_and_uninstall() {
+    let test_dir = TestDir::new("http-index-lifecycle");
+    for tag in ["4.2-stable", "4.2.1-stable", "4.3-stable"] {
+        add_release(&test_dir.releases_dir(), STABLE_REPO, tag, true);
+    }
+    let url = serve_dir(&test_dir.releases_dir());
+
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.2.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.2-stable")]
+    );
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &url),
+        Some(version_name("4.2-stable"))
+    );
+
+    let output = gdman(&test_dir, SOURCE, &url, &["update", "--patch"]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &url),
+        Some(version_name("4.2.1-stable"))
+    );
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &url,
+        &["update", "--minor", "--uninstall"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &url),
+        Some(version_name("4.3-stable"))
+    );
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.2-stable"), version_name("4.3-stable")]
+    );
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &url,
+        &["uninstall", "--version", "4.2.0"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+}
+
+#[test]
+fn install_latest() {
+    let test_dir = TestDir::new("http-index-latest");
+    for tag in ["4.2-stable", "4.3-stable"] {
+        add_release(&test_dir.releases_dir(), STABLE_REPO, tag, true);
+    }
+    let url = serve_dir(&test_dir.releases_dir());
+
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--latest"]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &url),
+        Some(version_name("4.3-stable"))
+    );
+}
+
+#[test]
+fn install_missing_version() {
+    let test_dir = TestDir::new("http-index-missing");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let url = serve_dir(&test_dir.releases_dir());
+
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.1.0"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(installed_versions(&test_dir).is_empty());
+}
+
+#[test]
+fn install_without_checksums() {
+    let test_dir = TestDir::new("http-index-no-checksums");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", false);
+    let url = serve_dir(&test_dir.releases_dir());
+
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.3.0"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(installed_versions(&test_dir).is_empty());
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &url,
+        &["install", "--version", "4.3.0", "--skip-verify"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+}
+
+#[test]
+fn resume_download() {
+    let test_dir = TestDir::new("http-index-resume");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let url = serve_dir(&test_dir.releases_dir());
+
+    let archive_name = format!("{}.zip", version_name("4.3-stable"));
+    let archive = fs::read(
+        test_dir
+            .releases_dir()
+            .join(STABLE_REPO)
+            .join("4.3-stable")
+            .join(&archive_name),
+    )
+    .unwrap();
+    // The test server's ETag is the size of the file
+    let etag = format!("\"{}\"", archive.len());
+
+    let downloads_dir = test_dir.home_dir().join("downloads");
+    fs::create_dir_all(&downloads_dir).unwrap();
+    let part_path = downloads_dir.join(format!("{archive_name}.part"));
+    let etag_path = downloads_dir.join(format!("{archive_name}.part.etag"));
+
+    // The start of the part file is kept, so if it's corrupt the checksum won't match
+    let mut corrupt = archive[..archive.len() / 2].to_vec();
+    corrupt[0] ^= 0xff;
+    fs::write(&part_path, &corrupt).unwrap();
+    fs::write(&etag_path, &etag).unwrap();
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.3.0"]);
+    assert_eq!(output.status.code(), Some(6));
+
+    // A part file from a different version of the file is downloaded again
+    fs::write(&part_path, &corrupt).unwrap();
+    fs::write(&etag_path, "\"outdated\"").unwrap();
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    fs::remove_dir_all(test_dir.versions_dir().join(version_name("4.3-stable"))).unwrap();
+
+    // A part file that's already complete isn't downloaded again
+    fs::write(&part_path, &archive).unwrap();
+    fs::write(&etag_path, &etag).unwrap();
+    let output = gdman(&test_dir, SOURCE, &url, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+}
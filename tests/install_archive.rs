@@ -0,0 +1,107 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::{
+    add_release, current_version, gdman, installed_versions, serve_dir, version_name, TestDir,
+    STABLE_REPO,
+};
+
+const SOURCE: &str = "local";
+
+/// Adds the release to the fixture releases, returning the path of its archive
+fn add_archive(test_dir: &TestDir, tag: &str) -> PathBuf {
+    add_release(&test_dir.releases_dir(), STABLE_REPO, tag, false);
+    return test_dir
+        .releases_dir()
+        .join(STABLE_REPO)
+        .join(tag)
+        .join(format!("{}.zip", version_name(tag)));
+}
+
+#[test]
+fn install_from_file() {
+    let test_dir = TestDir::new("archive-file");
+    let archive_path = add_archive(&test_dir, "4.3-stable");
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--from-file", &archive_path.to_string_lossy()],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.3-stable"))
+    );
+    assert!(archive_path.exists());
+}
+
+#[test]
+fn install_from_file_with_name() {
+    let test_dir = TestDir::new("archive-name");
+    let archive_path = test_dir.path.join("custom-build.zip");
+    fs::rename(add_archive(&test_dir, "4.3-stable"), &archive_path).unwrap();
+    let archive_arg = archive_path.to_string_lossy().into_owned();
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--from-file", &archive_arg],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(installed_versions(&test_dir).is_empty());
+
+    // The name needs to follow Godot's naming convention too
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &[
+            "install",
+            "--from-file",
+            &archive_arg,
+            "--name",
+            "studio-4.3-patched",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(installed_versions(&test_dir).is_empty());
+
+    let name = version_name("4.3-stable");
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--from-file", &archive_arg, "--name", &name],
+    );
+    assert!(output.status.success());
+    assert_eq!(installed_versions(&test_dir), vec![name.clone()]);
+    assert_eq!(current_version(&test_dir, SOURCE, &dir), Some(name));
+    assert!(archive_path.exists());
+}
+
+#[test]
+fn install_from_url() {
+    let test_dir = TestDir::new("archive-url");
+    add_archive(&test_dir, "4.3-stable");
+    let url = format!(
+        "{}{STABLE_REPO}/4.3-stable/{}.zip",
+        serve_dir(&test_dir.releases_dir()),
+        version_name("4.3-stable")
+    );
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--from-url", &url]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.3-stable"))
+    );
+}
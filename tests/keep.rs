@@ -0,0 +1,58 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    add_release, create_zip, gdman, installed_versions, platform_suffix, version_name, TestDir,
+    ZipEntry, STABLE_REPO,
+};
+
+const SOURCE: &str = "local";
+
+/// The name of the version for an architecture other than the one gdman is running on
+fn other_architecture_version_name(tag: &str) -> String {
+    let other_suffix = match platform_suffix() {
+        "win64.exe" => "win32.exe",
+        "linux.arm64" => "linux.x86_64",
+        _ => "linux.arm64",
+    };
+    return format!("Godot_v{tag}_{other_suffix}");
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn keeps_versions_of_each_architecture() {
+    let test_dir = TestDir::new("keep-architectures");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.2-stable", true);
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+    fs::write(test_dir.path.join("config.toml"), "keep = 1\n").unwrap();
+
+    let other_name = other_architecture_version_name("4.1-stable");
+    let archive_path = test_dir.path.join(format!("{other_name}.zip"));
+    fs::write(
+        &archive_path,
+        create_zip(&[ZipEntry::File(&other_name, b"Godot 4.1")]),
+    )
+    .unwrap();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["install", "--from-file", &archive_path.to_string_lossy()],
+    );
+    assert!(output.status.success());
+
+    for version in ["4.2.0", "4.3.0"] {
+        let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", version]);
+        assert!(output.status.success());
+    }
+
+    // Only the older version for the same architecture is uninstalled
+    let mut expected = vec![other_name, version_name("4.3-stable")];
+    expected.sort();
+    assert_eq!(installed_versions(&test_dir), expected);
+}
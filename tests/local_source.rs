@@ -0,0 +1,68 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    add_release, current_version, gdman, installed_versions, version_name, TestDir, STABLE_REPO,
+};
+
+const SOURCE: &str = "local";
+
+#[test]
+fn install_and_search() {
+    let test_dir = TestDir::new("local-install");
+    for tag in ["4.2-stable", "4.3-stable"] {
+        add_release(&test_dir.releases_dir(), STABLE_REPO, tag, true);
+    }
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.2.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &dir),
+        Some(version_name("4.2-stable"))
+    );
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["search", "--output", "json"]);
+    assert!(output.status.success());
+    let releases: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = releases
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![version_name("4.3-stable"), version_name("4.2-stable")]
+    );
+}
+
+#[test]
+fn install_tampered_archive() {
+    let test_dir = TestDir::new("local-tampered");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let archive_path = test_dir
+        .releases_dir()
+        .join(STABLE_REPO)
+        .join("4.3-stable")
+        .join(format!("{}.zip", version_name("4.3-stable")));
+    let mut archive = fs::read(&archive_path).unwrap();
+    archive.push(0);
+    fs::write(&archive_path, archive).unwrap();
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(installed_versions(&test_dir).is_empty());
+}
+
+#[test]
+fn missing_source_url() {
+    let test_dir = TestDir::new("local-missing-url");
+
+    let output = gdman(&test_dir, SOURCE, "", &["install", "--version", "4.3.0"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    add_release, gdman, installed_versions, serve_dir, version_name, TestDir, STABLE_REPO,
+};
+
+const SOURCE: &str = "local";
+
+#[test]
+fn install_from_mirror_with_failover() {
+    let test_dir = TestDir::new("mirrors-failover");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+
+    // The mirror has the genuine archive, while the release source's archive is corrupt,
+    // so the install only succeeds if the archive comes from the mirror
+    let archive_name = format!("{}.zip", version_name("4.3-stable"));
+    let archive_path = test_dir
+        .releases_dir()
+        .join(STABLE_REPO)
+        .join("4.3-stable")
+        .join(&archive_name);
+    let mirror_dir = test_dir.path.join("mirror");
+    fs::create_dir_all(mirror_dir.join("4.3")).unwrap();
+    fs::copy(&archive_path, mirror_dir.join("4.3").join(&archive_name)).unwrap();
+    fs::write(&archive_path, b"corrupt").unwrap();
+
+    let empty_dir = test_dir.path.join("empty");
+    fs::create_dir_all(&empty_dir).unwrap();
+    let mirrors = format!("{},{}", serve_dir(&empty_dir), serve_dir(&mirror_dir));
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["config", "set", "mirrors", &mirrors],
+    );
+    assert!(output.status.success());
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+}
+
+#[test]
+fn install_falls_back_to_release_source() {
+    let test_dir = TestDir::new("mirrors-fallback");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+
+    let empty_dir = test_dir.path.join("empty");
+    fs::create_dir_all(&empty_dir).unwrap();
+    let mirrors = serve_dir(&empty_dir);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["config", "set", "mirrors", &mirrors],
+    );
+    assert!(output.status.success());
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+}
+
+#[test]
+fn invalid_mirror() {
+    let test_dir = TestDir::new("mirrors-invalid");
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &dir,
+        &["config", "set", "mirrors", "godot-cdn,ftp://example.com"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,42 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs::{self, File};
+
+use common::{add_release, gdman, installed_versions, version_name, TestDir, STABLE_REPO};
+
+const SOURCE: &str = "local";
+
+#[test]
+fn removes_only_stale_staging_dirs() {
+    let test_dir = TestDir::new("staging-stale");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    let dir = test_dir.releases_dir().to_string_lossy().into_owned();
+
+    // One left by an interrupted install, whose lock file isn't held by anything,
+    // one from before staging directories had lock files, and one in use by another install
+    let staging_dir = test_dir.versions_dir().join(".staging");
+    for name in ["interrupted", "unlocked", "in-use"] {
+        fs::create_dir_all(staging_dir.join(name)).unwrap();
+        fs::write(staging_dir.join(name).join("godot"), "Godot").unwrap();
+    }
+    File::create(staging_dir.join("interrupted.lock")).unwrap();
+    let in_use_lock = File::create(staging_dir.join("in-use.lock")).unwrap();
+    in_use_lock.lock().unwrap();
+
+    let output = gdman(&test_dir, SOURCE, &dir, &["install", "--version", "4.3.0"]);
+    assert!(output.status.success());
+    assert_eq!(
+        installed_versions(&test_dir),
+        vec![version_name("4.3-stable")]
+    );
+
+    let mut remaining: Vec<String> = fs::read_dir(&staging_dir)
+        .unwrap()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    remaining.sort();
+    assert_eq!(remaining, vec!["in-use", "in-use.lock"]);
+}
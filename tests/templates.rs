@@ -0,0 +1,83 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fs;
+
+use common::{
+    add_release, add_templates, create_zip, current_version, gdman, serve_dir, templates_dir,
+    version_name, TestDir, ZipEntry, STABLE_REPO,
+};
+
+const SOURCE: &str = "http-index";
+
+#[test]
+fn install_with_templates_in_parallel() {
+    let test_dir = TestDir::new("templates-parallel");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", true);
+    add_templates(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable");
+    let url = serve_dir(&test_dir.releases_dir());
+    fs::write(test_dir.path.join("config.toml"), "parallelism = 2\n").unwrap();
+
+    let output = gdman(
+        &test_dir,
+        SOURCE,
+        &url,
+        &["install", "--version", "4.3.0", "--with-templates"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        current_version(&test_dir, SOURCE, &url),
+        Some(version_name("4.3-stable"))
+    );
+
+    let templates_dir = templates_dir(&test_dir, "4.3-stable");
+    assert_eq!(
+        fs::read_to_string(templates_dir.join("version.txt")).unwrap(),
+        "4.3-stable"
+    );
+
+    // Nothing is left behind by either download, including the checksums fetched for each
+    let downloads: Vec<_> = fs::read_dir(test_dir.home_dir().join("downloads"))
+        .map(|entries| entries.flatten().map(|e| e.file_name()).collect())
+        .unwrap_or_default();
+    assert!(downloads.is_empty(), "{downloads:?}");
+}
+
+#[test]
+fn failed_extraction_leaves_templates_uninstalled() {
+    let test_dir = TestDir::new("templates-failed-extraction");
+    add_release(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable", false);
+    let url = serve_dir(&test_dir.releases_dir());
+
+    // The first entry is extracted before the second is found to escape the templates directory
+    let archive = create_zip(&[
+        ZipEntry::File("templates/version.txt", b"4.3-stable"),
+        ZipEntry::File("templates/../../../../../../escaped", b"Escaped"),
+    ]);
+    let archive_path = test_dir
+        .releases_dir()
+        .join(STABLE_REPO)
+        .join("4.3-stable")
+        .join("Godot_v4.3-stable_export_templates.tpz");
+    fs::write(&archive_path, archive).unwrap();
+
+    let args = [
+        "templates",
+        "install",
+        "--version",
+        "4.3.0",
+        "--skip-verify",
+    ];
+    let output = gdman(&test_dir, SOURCE, &url, &args);
+    assert!(!output.status.success());
+    assert!(!templates_dir(&test_dir, "4.3-stable").exists());
+
+    // Installing again isn't skipped as though the templates were already installed
+    add_templates(&test_dir.releases_dir(), STABLE_REPO, "4.3-stable");
+    let output = gdman(&test_dir, SOURCE, &url, &args);
+    assert!(output.status.success());
+    assert!(templates_dir(&test_dir, "4.3-stable")
+        .join("linux_release.x86_64")
+        .is_file());
+}